@@ -0,0 +1,65 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use halo2_proofs::{
+    circuit::Value,
+    pasta::{EqAffine, Fp},
+    plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, SingleVerifier},
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+use rand_core::OsRng;
+use simple_example::{prove, MyCircuit};
+
+fn bench_prove(c: &mut Criterion) {
+    let constant = Fp::from(7);
+    let a = Fp::from(2);
+    let b = Fp::from(3);
+    let public_inputs = [constant * a.square() * b.square()];
+
+    let circuit = MyCircuit::new(constant, Value::known(a), Value::known(b));
+    let empty_circuit = circuit.without_witnesses();
+
+    // the parameters and keys are set up once, outside the measured closures
+    let params = prove::params();
+    let vk = keygen_vk(&params, &empty_circuit).unwrap();
+
+    c.bench_function("keygen_pk", |bench| {
+        bench.iter(|| keygen_pk(&params, vk.clone(), &empty_circuit).unwrap())
+    });
+
+    let pk = keygen_pk(&params, vk, &empty_circuit).unwrap();
+
+    c.bench_function("create_proof", |bench| {
+        bench.iter(|| {
+            let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]);
+            create_proof(
+                &params,
+                &pk,
+                std::slice::from_ref(&circuit),
+                &[&[&public_inputs]],
+                OsRng,
+                &mut transcript,
+            )
+            .unwrap();
+            transcript.finalize()
+        })
+    });
+
+    let proof = prove::prove(&circuit, &public_inputs).unwrap();
+
+    c.bench_function("verify_proof", |bench| {
+        bench.iter(|| {
+            let strategy = SingleVerifier::new(&params);
+            let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(&proof[..]);
+            verify_proof(
+                &params,
+                pk.get_vk(),
+                strategy,
+                &[&[&public_inputs]],
+                &mut transcript,
+            )
+            .unwrap()
+        })
+    });
+}
+
+criterion_group!(benches, bench_prove);
+criterion_main!(benches);
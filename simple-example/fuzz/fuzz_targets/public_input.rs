@@ -0,0 +1,32 @@
+//! Checks that `MyCircuit` only accepts the true public input `c = constant * a^2 * b^2`.
+
+#![no_main]
+
+use halo2_proofs::{
+    circuit::Value,
+    dev::MockProver,
+    pasta::{group::ff::PrimeField, Fp},
+};
+use libfuzzer_sys::fuzz_target;
+use simple_example::MyCircuit;
+
+fuzz_target!(|data: &[u8]| {
+    // interpret the bytes as the little-endian representation of a candidate `c'`, padding
+    // short inputs with zeros. Non-canonical representations aren't field elements.
+    let mut repr = <Fp as PrimeField>::Repr::default();
+    let len = data.len().min(repr.as_ref().len());
+    repr.as_mut()[..len].copy_from_slice(&data[..len]);
+    let Some(candidate) = Option::<Fp>::from(Fp::from_repr(repr)) else {
+        return;
+    };
+
+    let constant = Fp::from(7);
+    let a = Fp::from(2);
+    let b = Fp::from(3);
+    let c = constant * a.square() * b.square();
+
+    let circuit = MyCircuit::new(constant, Value::known(a), Value::known(b));
+    let prover = MockProver::run(4, &circuit, vec![vec![candidate]]).unwrap();
+
+    assert_eq!(prover.verify().is_ok(), candidate == c);
+});
@@ -0,0 +1,201 @@
+//! A chip computing many independent products, one per row.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+
+use crate::Number;
+
+#[derive(Debug, Clone)]
+pub struct BatchMulConfig {
+    advice: [Column<Advice>; 3],
+    s_mul: Selector,
+}
+
+// Unlike `FieldChip::mul`, which lays out the product in the row below its operands, this keeps
+// `a`, `b` and `c = a * b` side by side, so N products take N rows instead of 2N.
+pub struct BatchMulChip<F: Field> {
+    config: BatchMulConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> Chip<F> for BatchMulChip<F> {
+    type Config = BatchMulConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field> BatchMulChip<F> {
+    pub fn construct(config: BatchMulConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+    ) -> BatchMulConfig {
+        for column in &advice {
+            meta.enable_equality(*column);
+        }
+
+        let s_mul = meta.selector();
+
+        // create the row-wise multiplication gate
+        meta.create_gate("batch mul", |meta| {
+            // a0 | a1 | a2 | s_mul
+            //----------------
+            // a | b | c | s_mul
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let b = meta.query_advice(advice[1], Rotation::cur());
+            let c = meta.query_advice(advice[2], Rotation::cur());
+            let s_mul = meta.query_selector(s_mul);
+
+            // the polynomial is: s_mul * (a * b - c) == 0
+            vec![s_mul * (a * b - c)]
+        });
+
+        BatchMulConfig { advice, s_mul }
+    }
+
+    // load every pair along with its product into a single region, one row per pair, and return
+    // the products
+    pub fn batch_mul(
+        &self,
+        mut layouter: impl Layouter<F>,
+        pairs: &[(Value<F>, Value<F>)],
+    ) -> Result<Vec<Number<F>>, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "batch mul",
+            |mut region| {
+                pairs
+                    .iter()
+                    .enumerate()
+                    .map(|(row, &(a, b))| {
+                        config.s_mul.enable(&mut region, row)?;
+
+                        region.assign_advice(|| "a", config.advice[0], row, || a)?;
+                        region.assign_advice(|| "b", config.advice[1], row, || b)?;
+                        region
+                            .assign_advice(|| "a * b", config.advice[2], row, || a * b)
+                            .map(Number)
+                    })
+                    .collect()
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use halo2_proofs::{circuit::SimpleFloorPlanner, pasta::Fp, plonk::Circuit};
+
+    use crate::{
+        test_util::{assert_accepts, assert_rejects, configure},
+        FieldChip, FieldConfig, Instructions,
+    };
+
+    // Computes the product of each private pair with `BatchMulChip` and exposes the products as the
+    // public inputs, in order
+    #[derive(Default)]
+    pub struct BatchMulCircuit<F: Field> {
+        pairs: Vec<(Value<F>, Value<F>)>,
+    }
+
+    impl<F: Field> BatchMulCircuit<F> {
+        pub fn new(pairs: Vec<(Value<F>, Value<F>)>) -> Self {
+            Self { pairs }
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct BatchMulCircuitConfig {
+        field: FieldConfig,
+        batch: BatchMulConfig,
+    }
+
+    impl<F: Field> Circuit<F> for BatchMulCircuit<F> {
+        type Config = BatchMulCircuitConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                pairs: vec![(Value::unknown(), Value::unknown()); self.pairs.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let field = configure(meta);
+            let advice = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            let batch = BatchMulChip::configure(meta, advice);
+
+            BatchMulCircuitConfig { field, batch }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let field_chip = FieldChip::<F>::construct(config.field);
+            let batch_chip = BatchMulChip::<F>::construct(config.batch);
+
+            let products = batch_chip.batch_mul(layouter.namespace(|| "products"), &self.pairs)?;
+
+            for (row, product) in products.into_iter().enumerate() {
+                field_chip.expose_public(
+                    layouter.namespace(|| "expose product"),
+                    product,
+                    0,
+                    row,
+                )?;
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn batch_mul() {
+        let k = 5;
+
+        let pairs = (1..=10)
+            .map(|i| (Fp::from(i), Fp::from(i + 1)))
+            .collect::<Vec<_>>();
+        let mut products = pairs.iter().map(|(a, b)| a * b).collect::<Vec<_>>();
+
+        let circuit = BatchMulCircuit::new(
+            pairs
+                .iter()
+                .map(|&(a, b)| (Value::known(a), Value::known(b)))
+                .collect(),
+        );
+        assert_accepts(k, &circuit, vec![products.clone()]);
+
+        // negative case: every product is checked
+        products[9] += Fp::one();
+        assert_rejects(k, &circuit, vec![products]);
+    }
+}
@@ -0,0 +1,420 @@
+//! Example circuits built on `FieldChip`, alongside `MyCircuit`.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    pasta::group::ff::PrimeField,
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+
+use crate::{FieldChip, FieldConfig, Instructions};
+
+// create the columns shared by the circuits in this module
+fn configure<F: Field>(meta: &mut ConstraintSystem<F>) -> FieldConfig {
+    let advice = [meta.advice_column(), meta.advice_column()];
+    let instance = meta.instance_column();
+    let constant = meta.fixed_column();
+
+    FieldChip::configure(meta, advice, &[instance], constant)
+}
+
+// Exposes the product of all the private inputs as the public input at row 0.
+//
+// Each input takes one row to load and each of the `n - 1` multiplications takes two, so the
+// circuit needs roughly `3n` rows plus the blinding rows, e.g. `k = 5` for five inputs.
+#[derive(Default)]
+pub struct ProductCircuit<F: Field> {
+    inputs: Vec<Value<F>>,
+}
+
+impl<F: Field> ProductCircuit<F> {
+    pub fn new(inputs: Vec<Value<F>>) -> Self {
+        Self { inputs }
+    }
+}
+
+impl<F: Field> Circuit<F> for ProductCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            inputs: vec![Value::unknown(); self.inputs.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config);
+
+        let mut inputs = self.inputs.iter().enumerate().map(|(i, &input)| {
+            field_chip.load_private(layouter.namespace(|| format!("load input {}", i)), input)
+        });
+
+        // the empty product has nothing to fold over
+        let first = inputs.next().ok_or(Error::Synthesis)??;
+        let inputs = inputs.collect::<Result<Vec<_>, _>>()?;
+
+        let product = inputs
+            .into_iter()
+            .enumerate()
+            .try_fold(first, |acc, (i, input)| {
+                field_chip.mul(layouter.namespace(|| format!("mul {}", i)), acc, input)
+            })?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose product"), product, 0, 0)
+    }
+}
+
+// Exposes `p(x) = c0 + c1 * x + ... + cn * x^n` as the public input at row 0, evaluated with
+// Horner's rule: `p(x) = c0 + x * (c1 + x * (... + x * cn))`. The coefficients are constants and
+// `x` is private. An empty list of coefficients is the zero polynomial.
+#[derive(Default)]
+pub struct EvalPolyCircuit<F: Field> {
+    coeffs: Vec<F>,
+    x: Value<F>,
+}
+
+impl<F: Field> EvalPolyCircuit<F> {
+    pub fn new(coeffs: Vec<F>, x: Value<F>) -> Self {
+        Self { coeffs, x }
+    }
+}
+
+impl<F: Field> Circuit<F> for EvalPolyCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.coeffs.clone(), Value::unknown())
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config);
+
+        let x = field_chip.load_private(layouter.namespace(|| "load x"), self.x)?;
+
+        let mut coeffs = if self.coeffs.is_empty() {
+            vec![field_chip.load_zero(layouter.namespace(|| "load zero"))?]
+        } else {
+            field_chip.load_constants(layouter.namespace(|| "load coeffs"), &self.coeffs)?
+        };
+
+        // start from the leading coefficient and fold in the rest
+        let mut acc = coeffs.pop().ok_or(Error::Synthesis)?;
+        while let Some(coeff) = coeffs.pop() {
+            acc =
+                field_chip.mul_add(layouter.namespace(|| "acc * x + c"), acc, x.clone(), coeff)?;
+        }
+
+        field_chip.expose_public(layouter.namespace(|| "expose p(x)"), acc, 0, 0)
+    }
+}
+
+// Exposes `a^2 + b^2` as the public input at row 0, composing `square` and `add`
+#[derive(Default)]
+pub struct SumOfSquaresCircuit<F: Field> {
+    a: Value<F>,
+    b: Value<F>,
+}
+
+impl<F: Field> SumOfSquaresCircuit<F> {
+    pub fn new(a: Value<F>, b: Value<F>) -> Self {
+        Self { a, b }
+    }
+}
+
+impl<F: Field> Circuit<F> for SumOfSquaresCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config);
+
+        let a = field_chip.load_private(layouter.namespace(|| "load a"), self.a)?;
+        let b = field_chip.load_private(layouter.namespace(|| "load b"), self.b)?;
+
+        let asq = field_chip.square(layouter.namespace(|| "a * a"), a)?;
+        let bsq = field_chip.square(layouter.namespace(|| "b * b"), b)?;
+        let out = field_chip.add(layouter.namespace(|| "a^2 + b^2"), asq, bsq)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0, 0)
+    }
+}
+
+// the number of bits each factor of a `FactorizationCircuit` is range checked to
+pub const FACTOR_BITS: usize = 64;
+
+// Proves knowledge of private factors `a` and `b` of the public input `c` at row 0, without revealing
+// either factor. Both factors are constrained to differ from 1 and to fit in `FACTOR_BITS` bits, so
+// their product can't wrap around the field modulus and `c = c * 1` is not a valid proof.
+#[derive(Default)]
+pub struct FactorizationCircuit<F: Field> {
+    a: Value<F>,
+    b: Value<F>,
+}
+
+impl<F: Field> FactorizationCircuit<F> {
+    pub fn new(a: Value<F>, b: Value<F>) -> Self {
+        Self { a, b }
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for FactorizationCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config);
+
+        let a = field_chip.load_private(layouter.namespace(|| "load a"), self.a)?;
+        let b = field_chip.load_private(layouter.namespace(|| "load b"), self.b)?;
+
+        for (name, x) in [("a", &a), ("b", &b)] {
+            let mut layouter = layouter.namespace(|| name);
+
+            // x != 1
+            let diff =
+                field_chip.add_constant(layouter.namespace(|| "x - 1"), x.clone(), -F::ONE)?;
+            field_chip.assert_nonzero(layouter.namespace(|| "x - 1 != 0"), diff)?;
+
+            // x < 2^FACTOR_BITS
+            field_chip.decompose_bits(layouter.namespace(|| "range"), x.clone(), FACTOR_BITS)?;
+        }
+
+        let c = field_chip.mul(layouter.namespace(|| "a * b"), a, b)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose c"), c, 0, 0)
+    }
+}
+
+// Proves that the private `x` is a root of `a * x^2 + b * x + c`. The coefficients are part of the
+// circuit, so the verifier knows them from the verifying key.
+pub struct QuadraticRootCircuit<F: Field> {
+    x: Value<F>,
+    a: F,
+    b: F,
+    c: F,
+}
+
+impl<F: Field> QuadraticRootCircuit<F> {
+    pub fn new(x: Value<F>, a: F, b: F, c: F) -> Self {
+        Self { x, a, b, c }
+    }
+}
+
+impl<F: Field> Circuit<F> for QuadraticRootCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(Value::unknown(), self.a, self.b, self.c)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config);
+
+        let x = field_chip.load_private(layouter.namespace(|| "load x"), self.x)?;
+
+        let xsq = field_chip.square(layouter.namespace(|| "x * x"), x.clone())?;
+        let ax2 = field_chip.mul_constant(layouter.namespace(|| "a * x^2"), xsq, self.a)?;
+        let bx = field_chip.mul_constant(layouter.namespace(|| "b * x"), x, self.b)?;
+        let sum = field_chip.add(layouter.namespace(|| "a * x^2 + b * x"), ax2, bx)?;
+        let sum = field_chip.add_constant(layouter.namespace(|| "+ c"), sum, self.c)?;
+
+        field_chip.assert_equal_constant(layouter.namespace(|| "root"), sum, F::ZERO)
+    }
+}
+
+// Exposes the `n`th Fibonacci number, with `fib(1) = fib(2) = 1`, as the public input at row 0.
+// Each row of a single region holds three consecutive numbers `a | b | c`, and the next row
+// starts from `b | c`, so `n` is limited by the usable rows at the chosen `k`.
+#[derive(Default)]
+pub struct FibonacciCircuit<F: Field> {
+    n: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> FibonacciCircuit<F> {
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FibonacciConfig {
+    field: FieldConfig,
+    advice: [Column<Advice>; 3],
+    s_fib: Selector,
+}
+
+impl<F: Field> Circuit<F> for FibonacciCircuit<F> {
+    type Config = FibonacciConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.n)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let field = configure(meta);
+        let advice = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        for column in &advice {
+            meta.enable_equality(*column);
+        }
+        let s_fib = meta.selector();
+
+        meta.create_gate("fibonacci", |meta| {
+            // a0 | a1 | a2 | s_fib
+            //----------------
+            // a | b | c | s_fib
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let b = meta.query_advice(advice[1], Rotation::cur());
+            let c = meta.query_advice(advice[2], Rotation::cur());
+            let s_fib = meta.query_selector(s_fib);
+
+            // the polynomial is: s_fib * (a + b - c) == 0
+            vec![s_fib * (a + b - c)]
+        });
+
+        FibonacciConfig {
+            field,
+            advice,
+            s_fib,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        if self.n == 0 {
+            return Err(Error::Synthesis);
+        }
+
+        let field_chip = FieldChip::<F>::construct(config.field);
+        let [col_a, col_b, col_c] = config.advice;
+
+        let out = layouter.assign_region(
+            || "fibonacci",
+            |mut region| {
+                // the first row starts from the constants fib(1) and fib(2)
+                let a = region.assign_advice_from_constant(|| "fib 1", col_a, 0, F::ONE)?;
+                let b = region.assign_advice_from_constant(|| "fib 2", col_b, 0, F::ONE)?;
+                let mut out = if self.n == 1 { a.clone() } else { b.clone() };
+
+                // row `i` computes fib(i + 3) from the last two numbers of the row above
+                let mut prev = (a, b);
+                for row in 0..self.n.saturating_sub(2) {
+                    let (a, b) = if row == 0 {
+                        prev
+                    } else {
+                        (
+                            prev.0.copy_advice(|| "a", &mut region, col_a, row)?,
+                            prev.1.copy_advice(|| "b", &mut region, col_b, row)?,
+                        )
+                    };
+                    config.s_fib.enable(&mut region, row)?;
+
+                    let value = a.value().copied() + b.value();
+                    let c = region.assign_advice(|| "c", col_c, row, || value)?;
+
+                    out = c.clone();
+                    prev = (b, c);
+                }
+
+                Ok(out)
+            },
+        )?;
+
+        field_chip.expose_public_cell(layouter.namespace(|| "expose fib n"), out.cell(), 0, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use crate::test_util::{assert_accepts, assert_rejects};
+
+    #[test]
+    fn fibonacci() {
+        let k = 4;
+
+        let circuit = FibonacciCircuit::<Fp>::new(9);
+        assert_accepts(k, &circuit, vec![vec![Fp::from(34)]]);
+
+        // negative case
+        assert_rejects(k, &circuit, vec![vec![Fp::from(35)]]);
+
+        // the first numbers come straight from the constants
+        for (n, fib) in [(1, 1), (2, 1), (3, 2)] {
+            assert_accepts(k, &FibonacciCircuit::new(n), vec![vec![Fp::from(fib)]]);
+        }
+
+        // `n` is bounded by the usable rows
+        let circuit = FibonacciCircuit::<Fp>::new(20);
+        assert!(MockProver::run(k, &circuit, vec![vec![Fp::from(6765)]]).is_err());
+        assert_accepts(k + 1, &circuit, vec![vec![Fp::from(6765)]]);
+    }
+}
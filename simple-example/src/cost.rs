@@ -0,0 +1,65 @@
+//! Cost reporting for `MyCircuit`.
+
+use std::fmt;
+
+use halo2_proofs::{
+    dev::CircuitCost,
+    pasta::{Eq, Fp},
+    plonk::{Circuit, ConstraintSystem},
+};
+
+use crate::{FieldChip, MyCircuit};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CostReport {
+    pub gates: usize,
+    pub advice_columns: usize,
+    pub proof_size: usize,
+}
+
+impl fmt::Display for CostReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "gates: {}", self.gates)?;
+        writeln!(f, "advice columns: {}", self.advice_columns)?;
+        write!(f, "proof size: {} bytes", self.proof_size)
+    }
+}
+
+// measure the cost of `MyCircuit` laid out in 2^k rows
+pub fn cost_report(k: u32) -> CostReport {
+    let circuit = MyCircuit::<Fp>::default();
+    let cost = CircuitCost::<Eq, MyCircuit<Fp>>::measure(k, &circuit);
+
+    // `ConstraintSystem` keeps its gates `pub(crate)` in halo2_proofs 0.3, so they are counted
+    // from the descriptions of the `FieldChip`, the only chip `MyCircuit` configures. Each gate
+    // is described by one entry per constraint, in the order it was created.
+    let mut gates = FieldChip::<Fp>::gate_descriptions()
+        .into_iter()
+        .filter_map(|description| description.split(':').next().map(str::to_string))
+        .collect::<Vec<_>>();
+    gates.dedup();
+
+    let mut meta = ConstraintSystem::default();
+    let config = MyCircuit::<Fp>::configure(&mut meta);
+
+    CostReport {
+        gates: gates.len(),
+        advice_columns: config.advice.len(),
+        proof_size: cost.proof_size(1).into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report() {
+        let k = 4;
+
+        let report = cost_report(k);
+
+        assert_eq!(report.gates, 21);
+        assert_eq!(report.advice_columns, 2);
+    }
+}
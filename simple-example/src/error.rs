@@ -0,0 +1,64 @@
+//! The error type returned by the `FieldChip` instructions that take slices.
+
+use std::{error, fmt};
+
+use halo2_proofs::plonk;
+
+#[derive(Debug)]
+pub enum CircuitError {
+    // an error raised by halo2 itself
+    Synthesis(plonk::Error),
+    // two slices that must line up have different lengths
+    LengthMismatch { expected: usize, got: usize },
+    // a row of the instance column that can't be used
+    InvalidRow(usize),
+    // a slice that must hold at least one item is empty
+    Empty,
+    // the circuit at `index` doesn't share the constant of the first one
+    ConstantMismatch { index: usize },
+}
+
+impl fmt::Display for CircuitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitError::Synthesis(e) => write!(f, "synthesis failed: {}", e),
+            CircuitError::LengthMismatch { expected, got } => {
+                write!(f, "length mismatch: expected {}, got {}", expected, got)
+            }
+            CircuitError::InvalidRow(row) => write!(f, "invalid instance row {}", row),
+            CircuitError::Empty => write!(f, "expected at least one item"),
+            CircuitError::ConstantMismatch { index } => {
+                write!(f, "circuit {} has a different constant", index)
+            }
+        }
+    }
+}
+
+impl error::Error for CircuitError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            CircuitError::Synthesis(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<plonk::Error> for CircuitError {
+    fn from(e: plonk::Error) -> Self {
+        CircuitError::Synthesis(e)
+    }
+}
+
+// lets `synthesize` propagate a `CircuitError` with `?`. halo2 has no variant for our own
+// errors, so they surface as `Error::Synthesis`.
+impl From<CircuitError> for plonk::Error {
+    fn from(e: CircuitError) -> Self {
+        match e {
+            CircuitError::Synthesis(e) => e,
+            CircuitError::LengthMismatch { .. }
+            | CircuitError::InvalidRow(_)
+            | CircuitError::Empty
+            | CircuitError::ConstantMismatch { .. } => plonk::Error::Synthesis,
+        }
+    }
+}
@@ -0,0 +1,33 @@
+//! Shorthands for building field elements and private inputs from integers.
+
+use halo2_proofs::{circuit::Value, pasta::group::ff::PrimeField};
+
+// the field element `n`
+pub fn field<F: PrimeField>(n: u64) -> F {
+    F::from(n)
+}
+
+// the known private inputs `ns`
+pub fn values<F: PrimeField>(ns: &[u64]) -> Vec<Value<F>> {
+    ns.iter().map(|&n| Value::known(field(n))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use halo2_proofs::pasta::Fp;
+
+    use crate::{circuits::ProductCircuit, test_util::assert_accepts};
+
+    #[test]
+    fn field_ext() {
+        let k = 4;
+
+        assert_eq!(field::<Fp>(7), Fp::from(7));
+
+        // the helpers plug straight into the circuits
+        let circuit = ProductCircuit::<Fp>::new(values(&[2, 3]));
+        assert_accepts(k, &circuit, vec![vec![field(6)]]);
+    }
+}
@@ -0,0 +1,78 @@
+//! Visualizations of `MyCircuit`, available with the `dev-graph` feature.
+
+use std::{
+    error::Error,
+    io::{self, Write},
+    path::Path,
+};
+
+use halo2_proofs::{
+    dev::{circuit_dot_graph, CircuitLayout},
+    pasta::Fp,
+};
+use plotters::prelude::*;
+
+use crate::MyCircuit;
+
+// render the layout of `MyCircuit` laid out in 2^k rows to a PNG at `path`. The regions for
+// loading the private inputs, the multiplications, and the exposed output are labelled.
+pub fn render_layout(k: u32, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+    // the layout doesn't depend on the witnesses
+    let circuit = MyCircuit::<Fp>::default();
+
+    let root = BitMapBackend::new(path.as_ref(), (1024, 768)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let root = root.titled("MyCircuit Layout", ("sans-serif", 60))?;
+
+    CircuitLayout::default()
+        .show_labels(true)
+        .mark_equality_cells(true)
+        .show_equality_constraints(true)
+        .render(k, &circuit, &root)?;
+
+    root.present()?;
+
+    Ok(())
+}
+
+// write a Graphviz representation of the namespaces in `MyCircuit` to `writer`,
+// e.g. to pipe into `dot -Tsvg`
+pub fn dump_dot<W: Write>(writer: &mut W) -> io::Result<()> {
+    let circuit = MyCircuit::<Fp>::default();
+    writer.write_all(circuit_dot_graph(&circuit).as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::TempPath;
+
+    #[test]
+    fn renders_layout() -> Result<(), Box<dyn std::error::Error>> {
+        let k = 4;
+
+        let path = TempPath::new("layout.png");
+        render_layout(k, &path)?;
+        assert!(path.as_ref().exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn dumps_dot() -> Result<(), Box<dyn std::error::Error>> {
+        let mut dot = vec![];
+        dump_dot(&mut dot)?;
+        let dot = String::from_utf8(dot)?;
+
+        assert!(!dot.is_empty());
+        // the graph is built from the namespaces, not the region names
+        assert!(dot.contains("a * b"));
+        assert!(dot.contains("expose c"));
+        // every `mul` region gets its own indexed name
+        assert!(dot.contains("mul[3]"));
+        assert!(dot.contains("mul[5]"));
+
+        Ok(())
+    }
+}
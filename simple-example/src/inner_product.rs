@@ -0,0 +1,254 @@
+//! A chip computing an inner product with a single wide gate.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use crate::Number;
+
+#[derive(Debug, Clone)]
+pub struct InnerProductConfig<const W: usize> {
+    advice: [Column<Advice>; W],
+    s_ip: Selector,
+}
+
+// Unlike `Instructions::inner_product`, which chains a `mul_add` per pair, this lays out up to `W`
+// pairs and their inner product in a single region of 3 rows. It lives in its own chip because
+// the gate queries `Rotation(2)`, which grows the proof of every circuit configuring it.
+pub struct InnerProductChip<F: Field, const W: usize> {
+    config: InnerProductConfig<W>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field, const W: usize> Chip<F> for InnerProductChip<F, W> {
+    type Config = InnerProductConfig<W>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field, const W: usize> InnerProductChip<F, W> {
+    pub fn construct(config: InnerProductConfig<W>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; W],
+        constant: Column<Fixed>,
+    ) -> InnerProductConfig<W> {
+        // register the fixed column so that the floor planner can place the padding into it
+        meta.enable_constant(constant);
+        for column in &advice {
+            meta.enable_equality(*column);
+        }
+
+        let s_ip = meta.selector();
+
+        // create the wide inner product gate
+        meta.create_gate("inner_product", |meta| {
+            // a0 | a1 | .. | s_ip
+            //----------------
+            // x0 | x1 | .. | s_ip
+            // y0 | y1 | ..
+            // out
+            let xs = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            let ys = advice.map(|column| meta.query_advice(column, Rotation::next()));
+            let out = meta.query_advice(advice[0], Rotation(2));
+            let s_ip = meta.query_selector(s_ip);
+
+            // the polynomial is: s_ip * (x0 * y0 + x1 * y1 + .. - out) == 0
+            let sum = xs
+                .into_iter()
+                .zip(ys)
+                .fold(Expression::Constant(F::ZERO), |sum, (x, y)| sum + x * y);
+            vec![s_ip * (sum - out)]
+        });
+
+        InnerProductConfig { advice, s_ip }
+    }
+
+    // compute `sum_i xs[i] * ys[i]` and load into the circuit. `N` can be at most `W`; the
+    // remaining columns are padded with constant zeros.
+    pub fn inner_product_fixed<const N: usize>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        xs: [Number<F>; N],
+        ys: [Number<F>; N],
+    ) -> Result<Number<F>, Error> {
+        if N > W {
+            return Err(Error::Synthesis);
+        }
+
+        let config = self.config();
+
+        layouter.assign_region(
+            || "inner product",
+            |mut region| {
+                config.s_ip.enable(&mut region, 0)?;
+
+                // copy the advice values into the region, padding both rows with zeros
+                for (i, &column) in config.advice.iter().enumerate() {
+                    match (xs.get(i), ys.get(i)) {
+                        (Some(x), Some(y)) => {
+                            x.0.copy_advice(|| format!("x{}", i), &mut region, column, 0)?;
+                            y.0.copy_advice(|| format!("y{}", i), &mut region, column, 1)?;
+                        }
+                        _ => {
+                            region.assign_advice_from_constant(|| "pad", column, 0, F::ZERO)?;
+                            region.assign_advice_from_constant(|| "pad", column, 1, F::ZERO)?;
+                        }
+                    }
+                }
+
+                // out
+                let value = xs
+                    .iter()
+                    .zip(&ys)
+                    .fold(Value::known(F::ZERO), |sum, (x, y)| {
+                        sum + x.0.value().copied() * y.0.value()
+                    });
+                // assign `out` to advice column 0 at offset 2
+                region
+                    .assign_advice(|| "out", config.advice[0], 2, || value)
+                    .map(Number)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use halo2_proofs::{circuit::SimpleFloorPlanner, pasta::Fp, plonk::Circuit};
+
+    use crate::{
+        test_util::{assert_accepts, assert_rejects, layout},
+        FieldChip, FieldConfig, Instructions, MyCircuit,
+    };
+
+    // Exposes the inner product of the private inputs as the public input at row 0, computed with
+    // the wide `inner_product` gate over 4 advice columns, or with chained `mul_add`s
+    #[derive(Default)]
+    pub struct WideInnerProductCircuit<F: Field> {
+        xs: [Value<F>; 4],
+        ys: [Value<F>; 4],
+        chained: bool,
+    }
+
+    impl<F: Field> WideInnerProductCircuit<F> {
+        pub fn new(xs: [Value<F>; 4], ys: [Value<F>; 4]) -> Self {
+            Self {
+                xs,
+                ys,
+                chained: false,
+            }
+        }
+
+        pub fn chained(xs: [Value<F>; 4], ys: [Value<F>; 4]) -> Self {
+            Self {
+                xs,
+                ys,
+                chained: true,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct WideInnerProductConfig {
+        field: FieldConfig<4>,
+        inner_product: InnerProductConfig<4>,
+    }
+
+    impl<F: Field> Circuit<F> for WideInnerProductCircuit<F> {
+        type Config = WideInnerProductConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                chained: self.chained,
+                ..Self::default()
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let advice = [(); 4].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            let constant = meta.fixed_column();
+
+            let field = FieldChip::configure(meta, advice, &[instance], constant);
+            let inner_product = InnerProductChip::configure(meta, advice, constant);
+
+            WideInnerProductConfig {
+                field,
+                inner_product,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let field_chip = FieldChip::<F, 4>::construct(config.field);
+            let inner_product_chip = InnerProductChip::<F, 4>::construct(config.inner_product);
+
+            let xs = field_chip.load_private_many(layouter.namespace(|| "load xs"), &self.xs)?;
+            let ys = field_chip.load_private_many(layouter.namespace(|| "load ys"), &self.ys)?;
+
+            let out = if self.chained {
+                field_chip.inner_product(layouter.namespace(|| "xs . ys"), &xs, &ys)?
+            } else {
+                let xs = <[_; 4]>::try_from(xs).map_err(|_| Error::Synthesis)?;
+                let ys = <[_; 4]>::try_from(ys).map_err(|_| Error::Synthesis)?;
+                inner_product_chip.inner_product_fixed(layouter.namespace(|| "xs . ys"), xs, ys)?
+            };
+
+            field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0, 0)
+        }
+    }
+
+    #[test]
+    fn inner_product_fixed() {
+        let k = 5;
+
+        let xs = [1, 2, 3, 4].map(|x| Value::known(Fp::from(x)));
+        let ys = [5, 6, 7, 8].map(|y| Value::known(Fp::from(y)));
+        let out = Fp::from(5 + 12 + 21 + 32);
+
+        // the wide gate agrees with the chained `mul_add`s, in fewer rows
+        let wide = WideInnerProductCircuit::new(xs, ys);
+        let chained = WideInnerProductCircuit::chained(xs, ys);
+        for circuit in [&wide, &chained] {
+            assert_accepts(k, circuit, vec![vec![out]]);
+
+            // negative case
+            assert_rejects(k, circuit, vec![vec![out + Fp::one()]]);
+        }
+        let constants = |config: &WideInnerProductConfig| vec![config.field.constant];
+        assert!(layout(&wide, constants).rows() < layout(&chained, constants).rows());
+
+        // the gate's `Rotation(2)` query only costs the circuits configuring `InnerProductChip`
+        let mut meta = ConstraintSystem::<Fp>::default();
+        WideInnerProductCircuit::<Fp>::configure(&mut meta);
+        assert!(format!("{:?}", meta.pinned()).contains("Rotation(2)"));
+        let mut meta = ConstraintSystem::<Fp>::default();
+        MyCircuit::<Fp>::configure(&mut meta);
+        assert!(!format!("{:?}", meta.pinned()).contains("Rotation(2)"));
+    }
+}
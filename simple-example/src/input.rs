@@ -0,0 +1,112 @@
+//! Reading the `MyCircuit` witnesses from a JSON file.
+
+use std::{error, fmt, fs, io, path::Path};
+
+use halo2_proofs::{circuit::Value, pasta::group::ff::PrimeField};
+use serde::Deserialize;
+
+use crate::MyCircuit;
+
+#[derive(Debug)]
+pub enum InputError {
+    // the file couldn't be read
+    Io(io::Error),
+    // the file isn't valid JSON, or a field is missing or not a number
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Io(e) => write!(f, "failed to read the input file: {}", e),
+            InputError::Json(e) => write!(f, "invalid input: {}", e),
+        }
+    }
+}
+
+impl error::Error for InputError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            InputError::Io(e) => Some(e),
+            InputError::Json(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for InputError {
+    fn from(e: io::Error) -> Self {
+        InputError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for InputError {
+    fn from(e: serde_json::Error) -> Self {
+        InputError::Json(e)
+    }
+}
+
+// the shape of the input file, e.g. `{"constant": 7, "a": 2, "b": 3}`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Witnesses {
+    constant: u64,
+    a: u64,
+    b: u64,
+}
+
+impl<F: PrimeField> MyCircuit<F> {
+    // read the constant and the witnesses from a JSON file
+    pub fn from_json(path: impl AsRef<Path>) -> Result<Self, InputError> {
+        let json = fs::read_to_string(path)?;
+        let witnesses: Witnesses = serde_json::from_str(&json)?;
+
+        Ok(Self::new(
+            F::from(witnesses.constant),
+            Value::known(F::from(witnesses.a)),
+            Value::known(F::from(witnesses.b)),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use halo2_proofs::pasta::Fp;
+
+    use crate::test_util::{assert_accepts, TempPath};
+
+    #[test]
+    fn from_json() -> Result<(), Box<dyn std::error::Error>> {
+        let k = 4;
+
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/witness.json");
+        let circuit = MyCircuit::<Fp>::from_json(path)?;
+        assert_accepts(k, &circuit, vec![vec![Fp::from(7 * 4 * 9)]]);
+
+        // negative cases: malformed JSON and a missing field
+        for (name, json, expected) in [
+            ("malformed.json", "{\"constant\": 7,", "EOF while parsing"),
+            (
+                "missing.json",
+                "{\"constant\": 7, \"a\": 2}",
+                "missing field `b`",
+            ),
+        ] {
+            let path = TempPath::new(name);
+            std::fs::write(&path, json)?;
+
+            let err = MyCircuit::<Fp>::from_json(&path)
+                .err()
+                .ok_or("expected an error")?;
+            assert!(err.to_string().contains(expected), "{}", err);
+        }
+
+        assert!(matches!(
+            MyCircuit::<Fp>::from_json(TempPath::new("does-not-exist.json")),
+            Err(InputError::Io(_))
+        ));
+
+        Ok(())
+    }
+}
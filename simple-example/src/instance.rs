@@ -0,0 +1,117 @@
+//! Building circuits together with the public inputs they are checked against.
+
+use halo2_proofs::{arithmetic::Field, circuit::Value};
+use rand_core::RngCore;
+
+use crate::MyCircuit;
+
+// sample a `MyCircuit` with random `constant`, `a` and `b`, along with the public input it
+// produces
+pub fn random_instance<F: Field>(mut rng: impl RngCore) -> (MyCircuit<F>, Vec<F>) {
+    let constant = F::random(&mut rng);
+    let a = F::random(&mut rng);
+    let b = F::random(&mut rng);
+    let c = constant * a.square() * b.square();
+
+    (
+        MyCircuit::new(constant, Value::known(a), Value::known(b)),
+        vec![c],
+    )
+}
+
+// Assembles the instance columns passed to `MockProver::run` from `(column, row, value)` triples.
+// Rows that aren't set are zero.
+pub struct InstanceBuilder<F: Field> {
+    columns: Vec<Vec<F>>,
+}
+
+impl<F: Field> InstanceBuilder<F> {
+    // start building `columns` empty instance columns
+    pub fn new(columns: usize) -> Self {
+        Self {
+            columns: vec![vec![]; columns],
+        }
+    }
+
+    // set `row` of `column` to `value`, overwriting any earlier value
+    pub fn set(mut self, column: usize, row: usize, value: F) -> Self {
+        let column = self
+            .columns
+            .get_mut(column)
+            .unwrap_or_else(|| panic!("no instance column {}", column));
+        if column.len() <= row {
+            column.resize(row + 1, F::ZERO);
+        }
+        column[row] = value;
+        self
+    }
+
+    pub fn build(self) -> Vec<Vec<F>> {
+        self.columns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use halo2_proofs::{circuit::Layouter, pasta::Fp};
+    use rand_core::OsRng;
+
+    use crate::{
+        test_util::{assert_accepts, TestCircuit},
+        Instructions,
+    };
+
+    #[test]
+    fn random_instance_verifies() {
+        let k = 4;
+
+        for _ in 0..100 {
+            let (circuit, public_inputs) = random_instance::<Fp>(OsRng);
+            assert_accepts(k, &circuit, vec![public_inputs.clone()]);
+            assert_eq!(circuit.evaluate(), Some(public_inputs[0]));
+        }
+    }
+
+    #[test]
+    fn instance_builder() {
+        let k = 4;
+
+        let instance = InstanceBuilder::new(2)
+            .set(1, 1, Fp::from(4))
+            .set(0, 0, Fp::from(5))
+            .set(1, 0, Fp::from(6))
+            .set(0, 1, Fp::from(3))
+            .build();
+        assert_eq!(
+            instance,
+            vec![
+                vec![Fp::from(5), Fp::from(3)],
+                vec![Fp::from(6), Fp::from(4)],
+            ]
+        );
+
+        // exposes `a + b` and `a * b` in row 0 of each column, and `b` and `a * a` in row 1
+        let circuit = TestCircuit::<_, _, 2, 2>::with_columns(|field_chip, mut layouter| {
+            let a = Value::known(Fp::from(2));
+            let b = Value::known(Fp::from(3));
+            let a = field_chip.load_private(layouter.namespace(|| "load a"), a)?;
+            let b = field_chip.load_private(layouter.namespace(|| "load b"), b)?;
+
+            let sum = field_chip.add(layouter.namespace(|| "a + b"), a.clone(), b.clone())?;
+            let product = field_chip.mul(layouter.namespace(|| "a * b"), a.clone(), b.clone())?;
+            let square = field_chip.square(layouter.namespace(|| "a * a"), a)?;
+
+            field_chip.expose_public(layouter.namespace(|| "expose sum"), sum, 0, 0)?;
+            field_chip.expose_public(layouter.namespace(|| "expose b"), b, 0, 1)?;
+            field_chip.expose_public(layouter.namespace(|| "expose product"), product, 1, 0)?;
+            field_chip.expose_public(layouter.namespace(|| "expose square"), square, 1, 1)
+        });
+        assert_accepts(k, &circuit, instance);
+
+        // rows that aren't set are zero
+        let instance = InstanceBuilder::new(2).set(0, 1, Fp::one()).build();
+        assert_eq!(instance, vec![vec![Fp::zero(), Fp::one()], vec![]]);
+    }
+}
@@ -1,214 +1,2523 @@
-use std::marker::PhantomData;
+use std::{
+    fmt,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
 
 use halo2_proofs::{
     arithmetic::Field,
-    circuit::{AssignedCell, Chip, Layouter, SimpleFloorPlanner, Value},
-    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Instance, Selector},
+    circuit::{AssignedCell, Cell, Chip, Layouter, SimpleFloorPlanner, Value},
+    pasta::group::ff::PrimeField,
+    plonk::{
+        Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, FloorPlanner,
+        Instance, Selector,
+    },
     poly::Rotation,
 };
 
-trait Instructions<F: Field>: Chip<F> {
-    type Num;
+pub mod batch;
+pub mod circuits;
+pub mod cost;
+pub mod field_ext;
+#[cfg(feature = "dev-graph")]
+pub mod graph;
+pub mod inner_product;
+pub mod input;
+pub mod instance;
+pub mod macros;
+pub mod prove;
+pub mod range;
+mod rows;
+#[cfg(test)]
+mod test_util;
+#[cfg(test)]
+mod tests;
+pub mod witness;
+
+mod error;
+pub use error::CircuitError;
+
+pub trait Instructions<F: Field>: Chip<F> {
+    type Num;
+
+    fn load_private(&self, layouter: impl Layouter<F>, value: Value<F>)
+        -> Result<Self::Num, Error>;
+
+    fn load_private_many(
+        &self,
+        layouter: impl Layouter<F>,
+        values: &[Value<F>],
+    ) -> Result<Vec<Self::Num>, Error>;
+
+    fn load_private_bytes(
+        &self,
+        layouter: impl Layouter<F>,
+        bytes: &[u8],
+    ) -> Result<Self::Num, Error>
+    where
+        F: PrimeField;
+
+    fn load_constant(&self, layouter: impl Layouter<F>, constant: F) -> Result<Self::Num, Error>;
+
+    fn load_constants(
+        &self,
+        layouter: impl Layouter<F>,
+        constants: &[F],
+    ) -> Result<Vec<Self::Num>, Error>;
+
+    fn load_zero(&self, layouter: impl Layouter<F>) -> Result<Self::Num, Error>;
+
+    fn load_one(&self, layouter: impl Layouter<F>) -> Result<Self::Num, Error>;
+
+    fn load_public(&self, layouter: impl Layouter<F>, row: usize) -> Result<Self::Num, Error>;
+
+    fn add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+
+    fn sub(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+
+    fn mul(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+
+    fn square(&self, layouter: impl Layouter<F>, x: Self::Num) -> Result<Self::Num, Error>;
+
+    fn cube(&self, layouter: impl Layouter<F>, x: Self::Num) -> Result<Self::Num, Error>;
+
+    fn mul_add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+        c: Self::Num,
+    ) -> Result<Self::Num, Error>;
+
+    fn is_zero(&self, layouter: impl Layouter<F>, x: Self::Num) -> Result<Self::Num, Error>;
+
+    fn assert_bit(&self, layouter: impl Layouter<F>, x: Self::Num) -> Result<(), Error>;
+
+    fn select(
+        &self,
+        layouter: impl Layouter<F>,
+        cond: Self::Num,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+
+    fn assert_equal(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<(), Error>;
+
+    fn add_constant(
+        &self,
+        layouter: impl Layouter<F>,
+        x: Self::Num,
+        constant: F,
+    ) -> Result<Self::Num, Error>;
+
+    fn mul_constant(
+        &self,
+        layouter: impl Layouter<F>,
+        x: Self::Num,
+        constant: F,
+    ) -> Result<Self::Num, Error>;
+
+    fn inner_product(
+        &self,
+        layouter: impl Layouter<F>,
+        xs: &[Self::Num],
+        ys: &[Self::Num],
+    ) -> Result<Self::Num, CircuitError>;
+
+    fn pow_const(
+        &self,
+        layouter: impl Layouter<F>,
+        x: Self::Num,
+        exp: u64,
+    ) -> Result<Self::Num, Error>;
+
+    fn div(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+
+    fn inv(&self, layouter: impl Layouter<F>, x: Self::Num) -> Result<Self::Num, Error>;
+
+    fn sum(&self, layouter: impl Layouter<F>, xs: &[Self::Num]) -> Result<Self::Num, Error>;
+
+    fn running_sum(&self, layouter: impl Layouter<F>, xs: &[Value<F>]) -> Result<Self::Num, Error>;
+
+    fn running_product(
+        &self,
+        layouter: impl Layouter<F>,
+        xs: &[Value<F>],
+    ) -> Result<Self::Num, Error>;
+
+    fn decompose_bits(
+        &self,
+        layouter: impl Layouter<F>,
+        x: Self::Num,
+        n: usize,
+    ) -> Result<Vec<Self::Num>, Error>
+    where
+        F: PrimeField;
+
+    fn is_equal_constant(
+        &self,
+        layouter: impl Layouter<F>,
+        x: Self::Num,
+        k: F,
+    ) -> Result<Self::Num, Error>;
+
+    fn neg(&self, layouter: impl Layouter<F>, x: Self::Num) -> Result<Self::Num, Error>;
+
+    fn double(&self, layouter: impl Layouter<F>, x: Self::Num) -> Result<Self::Num, Error>;
+
+    fn assert_nonzero(&self, layouter: impl Layouter<F>, x: Self::Num) -> Result<(), Error>;
+
+    fn compute(&self, layouter: impl Layouter<F>, ops: &[Op<F>]) -> Result<Self::Num, Error>;
+
+    fn fold(
+        &self,
+        layouter: impl Layouter<F>,
+        xs: &[Self::Num],
+        op: FoldOp,
+    ) -> Result<Self::Num, Error>;
+
+    fn shift_left(
+        &self,
+        layouter: impl Layouter<F>,
+        x: Self::Num,
+        k: u32,
+    ) -> Result<Self::Num, Error>;
+
+    fn expose_public_cell(
+        &self,
+        layouter: impl Layouter<F>,
+        cell: Cell,
+        column: usize,
+        row: usize,
+    ) -> Result<(), Error>;
+
+    fn load_constant_gated(&self, layouter: impl Layouter<F>, k: F) -> Result<Self::Num, Error>;
+
+    fn assert_linear_combo(
+        &self,
+        layouter: impl Layouter<F>,
+        terms: &[(F, Self::Num)],
+        rhs: F,
+    ) -> Result<(), Error>;
+
+    fn assert_congruent(
+        &self,
+        layouter: impl Layouter<F>,
+        x: Self::Num,
+        y: Self::Num,
+        modulus: F,
+    ) -> Result<(), Error>
+    where
+        F: PrimeField;
+
+    fn sqrt(&self, layouter: impl Layouter<F>, x: Self::Num) -> Result<Self::Num, Error>;
+
+    fn weighted_mean(
+        &self,
+        layouter: impl Layouter<F>,
+        xs: &[Self::Num],
+        weights: &[F],
+    ) -> Result<Self::Num, CircuitError>;
+
+    fn assert_in_set(
+        &self,
+        layouter: impl Layouter<F>,
+        x: Self::Num,
+        allowed: &[F],
+    ) -> Result<(), Error>;
+
+    fn check_commitment(
+        &self,
+        layouter: impl Layouter<F>,
+        m: Self::Num,
+        r: Self::Num,
+        g: F,
+        h: F,
+        commitment: Self::Num,
+    ) -> Result<(), Error>;
+
+    fn mul_and_expose(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+        row: usize,
+    ) -> Result<Self::Num, Error>;
+
+    fn parity(
+        &self,
+        layouter: impl Layouter<F>,
+        x: Self::Num,
+        n: usize,
+    ) -> Result<Self::Num, Error>
+    where
+        F: PrimeField;
+
+    fn max(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+        n: usize,
+    ) -> Result<Self::Num, Error>
+    where
+        F: PrimeField;
+
+    fn assert_equal_constant(
+        &self,
+        layouter: impl Layouter<F>,
+        x: Self::Num,
+        k: F,
+    ) -> Result<(), Error>;
+
+    fn assert_product_equal(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+        c: Self::Num,
+        d: Self::Num,
+    ) -> Result<(), Error>;
+
+    fn expose_public(
+        &self,
+        layouter: impl Layouter<F>,
+        num: Self::Num,
+        column: usize,
+        row: usize,
+    ) -> Result<(), Error>;
+
+    fn expose_public_many(
+        &self,
+        layouter: impl Layouter<F>,
+        nums: &[Self::Num],
+        start_row: usize,
+    ) -> Result<(), CircuitError>;
+}
+
+// `W` is the number of advice columns. The gates only use the first two, but wider
+// configurations leave room for multi-input gates, such as the one of `InnerProductChip`.
+#[derive(Debug, Clone)]
+pub struct FieldConfig<const W: usize = 2> {
+    advice: [Column<Advice>; W],
+    // `expose_public` takes an index into these. `load_public` and `expose_public_many` use the
+    // first one.
+    instance: Vec<Column<Instance>>,
+    // registered via `enable_constant`, so the floor planner also places constants here
+    constant: Column<Fixed>,
+    // the mode of each `op` region. Kept apart from `constant`, so that the modes don't take up
+    // the rows the floor planner would place constants in.
+    mode: Column<Fixed>,
+    s_op: Selector,
+    s_sub: Selector,
+    s_square: Selector,
+    s_mul_add: Selector,
+    s_is_zero: Selector,
+    s_bool: Selector,
+    s_select: Selector,
+    s_add_const: Selector,
+    s_mul_const: Selector,
+    s_div: Selector,
+    s_inv: Selector,
+    s_acc: Selector,
+    s_bits: Selector,
+    s_cube: Selector,
+    s_neg: Selector,
+    s_double: Selector,
+    s_const: Selector,
+    s_lin: Selector,
+    s_sqrt: Selector,
+    s_in_set: Selector,
+    s_prod: Selector,
+}
+
+/// A chip implementing the arithmetic [`Instructions`] over `W` advice columns.
+///
+/// Circuits configure the chip once and construct it from that configuration during synthesis:
+///
+/// ```
+/// use halo2_proofs::{
+///     circuit::{Layouter, SimpleFloorPlanner, Value},
+///     dev::MockProver,
+///     pasta::Fp,
+///     plonk::{Circuit, ConstraintSystem, Error},
+/// };
+/// use simple_example::{FieldChip, FieldConfig, Instructions};
+///
+/// #[derive(Default)]
+/// struct Square(Value<Fp>);
+///
+/// impl Circuit<Fp> for Square {
+///     type Config = FieldConfig;
+///     type FloorPlanner = SimpleFloorPlanner;
+///
+///     fn without_witnesses(&self) -> Self {
+///         Self::default()
+///     }
+///
+///     fn configure(meta: &mut ConstraintSystem<Fp>) -> FieldConfig {
+///         let advice = [meta.advice_column(), meta.advice_column()];
+///         let instance = meta.instance_column();
+///         let constant = meta.fixed_column();
+///         FieldChip::configure(meta, advice, &[instance], constant)
+///     }
+///
+///     fn synthesize(
+///         &self,
+///         config: FieldConfig,
+///         mut layouter: impl Layouter<Fp>,
+///     ) -> Result<(), Error> {
+///         let chip = FieldChip::<Fp>::construct(config);
+///         let x = chip.load_private(layouter.namespace(|| "load x"), self.0)?;
+///         let xsq = chip.square(layouter.namespace(|| "x * x"), x)?;
+///         chip.expose_public(layouter.namespace(|| "expose xsq"), xsq, 0, 0)
+///     }
+/// }
+///
+/// let circuit = Square(Value::known(Fp::from(3)));
+/// let prover = MockProver::run(4, &circuit, vec![vec![Fp::from(9)]]).unwrap();
+/// assert_eq!(prover.verify(), Ok(()));
+/// ```
+#[derive(Clone)]
+pub struct FieldChip<F, const W: usize = 2> {
+    config: FieldConfig<W>,
+    // the number of regions laid out so far, used to give each one a distinct name. Clones share
+    // it, so regions laid out through a clone are numbered after the original's.
+    regions: Arc<AtomicUsize>,
+    _marker: PhantomData<F>,
+}
+
+impl<F, const W: usize> fmt::Debug for FieldChip<F, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FieldChip")
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl<F: Field, const W: usize> Chip<F> for FieldChip<F, W> {
+    type Config = FieldConfig<W>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field, const W: usize> FieldChip<F, W> {
+    // the rows of the region laid out by `load_private`, `load_constant` and `load_public`
+    pub const LOAD_ROWS: usize = 1;
+    // the rows of the region laid out by `add` and `mul`, which share the `op` gate
+    pub const ADD_ROWS: usize = 2;
+    pub const MUL_ROWS: usize = 2;
+    // the rows of the region laid out by `square`
+    pub const SQUARE_ROWS: usize = 2;
+    // the bits of the quotient witnessed by `assert_congruent`
+    pub const QUOTIENT_BITS: usize = 16;
+    // the largest set accepted by `assert_in_set`. Every allowed value adds a factor to the
+    // `in_set` gate, and 3 keeps its degree within that of the other gates.
+    pub const MAX_SET_SIZE: usize = 3;
+
+    // a human-readable form of every polynomial constrained by the gates of the chip, as
+    // `"<gate>: <polynomial>"`. Each polynomial must equal zero wherever its selector is enabled.
+    pub fn gate_descriptions() -> Vec<String> {
+        let in_set = (0..Self::MAX_SET_SIZE)
+            .map(|i| format!(" * (x - v{})", i))
+            .collect::<String>();
+        let in_set = format!("s_in_set{}", in_set);
+
+        [
+            (
+                "op",
+                "s_op * (mode * (lhs * rhs) + (1 - mode) * (lhs + rhs) - out)",
+            ),
+            ("op", "s_op * (mode * (1 - mode))"),
+            ("sub", "s_sub * (lhs - rhs - out)"),
+            ("square", "s_square * (x * x - out)"),
+            ("mul_add", "s_mul_add * (a * b + c - out)"),
+            ("is_zero", "s_is_zero * (x * inv - (1 - out))"),
+            ("is_zero", "s_is_zero * (x * out)"),
+            ("bool", "s_bool * (x * (1 - x))"),
+            ("select", "s_select * (cond * (a - b) + b - out)"),
+            ("add_const", "s_add_const * (x + k - out)"),
+            ("mul_const", "s_mul_const * (k * x - out)"),
+            ("div", "s_div * (q * b - a)"),
+            ("div", "s_div * (b * b_inv - 1)"),
+            ("inv", "s_inv * (x * inv - 1)"),
+            ("acc", "s_acc * (acc_prev + x - acc)"),
+            ("bits", "s_bits * (2 * acc_prev + bit - acc)"),
+            ("cube", "s_cube * (x * x - t)"),
+            ("cube", "s_cube * (t * x - out)"),
+            ("neg", "s_neg * (x + out)"),
+            ("double", "s_double * (2 * x - out)"),
+            ("const", "s_const * (x - k)"),
+            ("lin", "s_lin * (acc_prev + coeff * x - acc)"),
+            ("sqrt", "s_sqrt * (r * r - x)"),
+            ("in_set", in_set.as_str()),
+            ("prod", "s_prod * (acc_prev * x - acc)"),
+        ]
+        .iter()
+        .map(|(gate, polynomial)| format!("{}: {}", gate, polynomial))
+        .collect()
+    }
+
+    pub fn construct(config: <Self as Chip<F>>::Config) -> Self {
+        Self {
+            config,
+            regions: Arc::new(AtomicUsize::new(0)),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; W],
+        instance: &[Column<Instance>],
+        constant: Column<Fixed>,
+    ) -> <Self as Chip<F>>::Config {
+        assert!(W >= 2, "FieldChip needs at least two advice columns");
+        assert!(!instance.is_empty(), "FieldChip needs an instance column");
+
+        for column in instance {
+            meta.enable_equality(*column);
+        }
+        // register the fixed column so that the floor planner can place constants into it
+        meta.enable_constant(constant);
+        for column in &advice {
+            meta.enable_equality(*column);
+        }
+
+        let mode = meta.fixed_column();
+
+        let s_op = meta.selector();
+        let s_sub = meta.selector();
+        let s_square = meta.selector();
+        let s_mul_add = meta.selector();
+        let s_is_zero = meta.selector();
+        let s_bool = meta.selector();
+        let s_select = meta.selector();
+        let s_add_const = meta.selector();
+        let s_mul_const = meta.selector();
+        let s_div = meta.selector();
+        let s_inv = meta.selector();
+        let s_acc = meta.selector();
+        let s_bits = meta.selector();
+        let s_cube = meta.selector();
+        let s_neg = meta.selector();
+        let s_double = meta.selector();
+        let s_const = meta.selector();
+        let s_lin = meta.selector();
+        let s_sqrt = meta.selector();
+        let s_in_set = meta.selector();
+        let s_prod = meta.selector();
+
+        // create the combined addition and multiplication gate. The mode column holds the mode
+        // of each region: 0 for addition, 1 for multiplication.
+        meta.create_gate("op", |meta| {
+            // a0 | a1 | f1 | s_op
+            //----------------
+            // lhs | rhs | mode | s_op
+            // out
+            let lhs = meta.query_advice(advice[0], Rotation::cur());
+            let rhs = meta.query_advice(advice[1], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let mode = meta.query_fixed(mode);
+            let s_op = meta.query_selector(s_op);
+            let one = Expression::Constant(F::ONE);
+
+            // the polynomials are:
+            // s_op * (mode * (lhs * rhs) + (1 - mode) * (lhs + rhs) - out) == 0
+            // s_op * (mode * (1 - mode)) == 0
+            vec![
+                s_op.clone()
+                    * (mode.clone() * (lhs.clone() * rhs.clone())
+                        + (one.clone() - mode.clone()) * (lhs + rhs)
+                        - out),
+                s_op * (mode.clone() * (one - mode)),
+            ]
+        });
+
+        // create the subtraction gate
+        meta.create_gate("sub", |meta| {
+            // a0 | a1 | s_sub
+            //----------------
+            // lhs | rhs | s_sub
+            // out
+            let lhs = meta.query_advice(advice[0], Rotation::cur());
+            let rhs = meta.query_advice(advice[1], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_sub = meta.query_selector(s_sub);
+
+            // the polynomial is: s_sub * (lhs - rhs - out) == 0
+            vec![s_sub * (lhs - rhs - out)]
+        });
+
+        // create the squaring gate
+        meta.create_gate("square", |meta| {
+            // a0 | s_square
+            //----------------
+            // x | s_square
+            // out
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_square = meta.query_selector(s_square);
+
+            // the polynomial is: s_square * (x * x - out) == 0
+            vec![s_square * (x.clone() * x - out)]
+        });
+
+        // create the fused multiply-add gate
+        meta.create_gate("mul_add", |meta| {
+            // a0 | a1 | s_mul_add
+            //----------------
+            // a | b | s_mul_add
+            // out | c
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let b = meta.query_advice(advice[1], Rotation::cur());
+            let c = meta.query_advice(advice[1], Rotation::next());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_mul_add = meta.query_selector(s_mul_add);
+
+            // the polynomial is: s_mul_add * (a * b + c - out) == 0
+            vec![s_mul_add * (a * b + c - out)]
+        });
+
+        // create the is-zero gate
+        meta.create_gate("is_zero", |meta| {
+            // a0 | a1 | s_is_zero
+            //----------------
+            // x | inv | s_is_zero
+            // out
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            let inv = meta.query_advice(advice[1], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_is_zero = meta.query_selector(s_is_zero);
+            let one = Expression::Constant(F::ONE);
+
+            // the polynomials are:
+            // s_is_zero * (x * inv - (1 - out)) == 0
+            // s_is_zero * (x * out) == 0
+            //
+            // if x != 0, the first forces out == 0 (given inv = 1/x). If x == 0, the first
+            // forces out == 1, and the second then holds trivially.
+            vec![
+                s_is_zero.clone() * (x.clone() * inv - (one - out.clone())),
+                s_is_zero * (x * out),
+            ]
+        });
+
+        // create the boolean gate
+        meta.create_gate("bool", |meta| {
+            // a0 | s_bool
+            //----------------
+            // x | s_bool
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            let s_bool = meta.query_selector(s_bool);
+            let one = Expression::Constant(F::ONE);
+
+            // the polynomial is: s_bool * (x * (1 - x)) == 0
+            vec![s_bool * (x.clone() * (one - x))]
+        });
+
+        // create the select gate
+        meta.create_gate("select", |meta| {
+            // a0 | a1 | s_select
+            //----------------
+            // cond | a | s_select
+            // b | out
+            let cond = meta.query_advice(advice[0], Rotation::cur());
+            let a = meta.query_advice(advice[1], Rotation::cur());
+            let b = meta.query_advice(advice[0], Rotation::next());
+            let out = meta.query_advice(advice[1], Rotation::next());
+            let s_select = meta.query_selector(s_select);
+
+            // the polynomial is: s_select * (cond * (a - b) + b - out) == 0
+            vec![s_select * (cond * (a - b.clone()) + b - out)]
+        });
+
+        // create the add-constant gate
+        meta.create_gate("add_const", |meta| {
+            // a0 | f0 | s_add_const
+            //----------------
+            // x | k | s_add_const
+            // out
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            let k = meta.query_fixed(constant);
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_add_const = meta.query_selector(s_add_const);
+
+            // the polynomial is: s_add_const * (x + k - out) == 0
+            vec![s_add_const * (x + k - out)]
+        });
+
+        // create the multiply-by-constant gate
+        meta.create_gate("mul_const", |meta| {
+            // a0 | f0 | s_mul_const
+            //----------------
+            // x | k | s_mul_const
+            // out
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            let k = meta.query_fixed(constant);
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_mul_const = meta.query_selector(s_mul_const);
+
+            // the polynomial is: s_mul_const * (k * x - out) == 0
+            vec![s_mul_const * (k * x - out)]
+        });
+
+        // create the division gate
+        meta.create_gate("div", |meta| {
+            // a0 | a1 | s_div
+            //----------------
+            // a | b     | s_div
+            // q | b_inv
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let b = meta.query_advice(advice[1], Rotation::cur());
+            let q = meta.query_advice(advice[0], Rotation::next());
+            let b_inv = meta.query_advice(advice[1], Rotation::next());
+            let s_div = meta.query_selector(s_div);
+            let one = Expression::Constant(F::ONE);
+
+            // the polynomials are: s_div * (q * b - a) == 0 and s_div * (b * b_inv - 1) == 0.
+            // Without the second one, `q` would be unconstrained when `a = b = 0`.
+            vec![
+                s_div.clone() * (q * b.clone() - a),
+                s_div * (b * b_inv - one),
+            ]
+        });
+
+        // create the inverse gate
+        meta.create_gate("inv", |meta| {
+            // a0 | s_inv
+            //----------------
+            // x | s_inv
+            // inv
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            let inv = meta.query_advice(advice[0], Rotation::next());
+            let s_inv = meta.query_selector(s_inv);
+            let one = Expression::Constant(F::ONE);
+
+            // the polynomial is: s_inv * (x * inv - 1) == 0
+            vec![s_inv * (x * inv - one)]
+        });
+
+        // create the running sum gate
+        meta.create_gate("acc", |meta| {
+            // a0 | a1 | s_acc
+            //----------------
+            //    | acc_prev |
+            // x | acc | s_acc
+            let acc_prev = meta.query_advice(advice[1], Rotation::prev());
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            let acc = meta.query_advice(advice[1], Rotation::cur());
+            let s_acc = meta.query_selector(s_acc);
+
+            // the polynomial is: s_acc * (acc_prev + x - acc) == 0
+            vec![s_acc * (acc_prev + x - acc)]
+        });
+
+        // create the bit recomposition gate, consuming the bits most significant first
+        meta.create_gate("bits", |meta| {
+            // a0 | a1 | s_bits
+            //----------------
+            //    | acc_prev |
+            // bit | acc | s_bits
+            let acc_prev = meta.query_advice(advice[1], Rotation::prev());
+            let bit = meta.query_advice(advice[0], Rotation::cur());
+            let acc = meta.query_advice(advice[1], Rotation::cur());
+            let s_bits = meta.query_selector(s_bits);
+            let two = Expression::Constant(F::ONE.double());
+
+            // the polynomial is: s_bits * (2 * acc_prev + bit - acc) == 0
+            vec![s_bits * (two * acc_prev + bit - acc)]
+        });
+
+        // create the cubing gate
+        meta.create_gate("cube", |meta| {
+            // a0 | a1 | s_cube
+            //----------------
+            // x | t | s_cube
+            // out
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            let t = meta.query_advice(advice[1], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_cube = meta.query_selector(s_cube);
+
+            // the polynomials are:
+            // s_cube * (x * x - t) == 0
+            // s_cube * (t * x - out) == 0
+            vec![
+                s_cube.clone() * (x.clone() * x.clone() - t.clone()),
+                s_cube * (t * x - out),
+            ]
+        });
+
+        // create the negation gate
+        meta.create_gate("neg", |meta| {
+            // a0 | s_neg
+            //----------------
+            // x | s_neg
+            // out
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_neg = meta.query_selector(s_neg);
+
+            // the polynomial is: s_neg * (x + out) == 0
+            vec![s_neg * (x + out)]
+        });
+
+        // create the doubling gate
+        meta.create_gate("double", |meta| {
+            // a0 | s_double
+            //----------------
+            // x | s_double
+            // out
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_double = meta.query_selector(s_double);
+            let two = Expression::Constant(F::ONE.double());
+
+            // the polynomial is: s_double * (2 * x - out) == 0
+            vec![s_double * (two * x - out)]
+        });
+
+        // create the gated constant gate, binding an advice cell to the fixed column without the
+        // equality constraint used by `load_constant`
+        meta.create_gate("const", |meta| {
+            // a0 | f0 | s_const
+            //----------------
+            // x | k | s_const
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            let k = meta.query_fixed(constant);
+            let s_const = meta.query_selector(s_const);
+
+            // the polynomial is: s_const * (x - k) == 0
+            vec![s_const * (x - k)]
+        });
+
+        // create the linear combination gate, accumulating one weighted term per row
+        meta.create_gate("lin", |meta| {
+            // a0 | a1 | f0 | s_lin
+            //----------------
+            //    | acc_prev |
+            // x | acc | coeff | s_lin
+            let acc_prev = meta.query_advice(advice[1], Rotation::prev());
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            let acc = meta.query_advice(advice[1], Rotation::cur());
+            let coeff = meta.query_fixed(constant);
+            let s_lin = meta.query_selector(s_lin);
+
+            // the polynomial is: s_lin * (acc_prev + coeff * x - acc) == 0
+            vec![s_lin * (acc_prev + coeff * x - acc)]
+        });
+
+        // create the square root gate
+        meta.create_gate("sqrt", |meta| {
+            // a0 | s_sqrt
+            //----------------
+            // x | s_sqrt
+            // r
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            let r = meta.query_advice(advice[0], Rotation::next());
+            let s_sqrt = meta.query_selector(s_sqrt);
+
+            // the polynomial is: s_sqrt * (r * r - x) == 0
+            vec![s_sqrt * (r.clone() * r - x)]
+        });
+
+        // create the set membership gate
+        meta.create_gate("in_set", |meta| {
+            // a0 | a1 | s_in_set
+            //----------------
+            // x | v0 | s_in_set
+            // v1 | v2
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            let v0 = meta.query_advice(advice[1], Rotation::cur());
+            let v1 = meta.query_advice(advice[0], Rotation::next());
+            let v2 = meta.query_advice(advice[1], Rotation::next());
+            let s_in_set = meta.query_selector(s_in_set);
+
+            // the polynomial is: s_in_set * (x - v0) * (x - v1) * (x - v2) == 0
+            vec![s_in_set * (x.clone() - v0) * (x.clone() - v1) * (x - v2)]
+        });
+
+        // create the running product gate
+        meta.create_gate("prod", |meta| {
+            // a0 | a1 | s_prod
+            //----------------
+            //    | acc_prev |
+            // x | acc | s_prod
+            let acc_prev = meta.query_advice(advice[1], Rotation::prev());
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            let acc = meta.query_advice(advice[1], Rotation::cur());
+            let s_prod = meta.query_selector(s_prod);
+
+            // the polynomial is: s_prod * (acc_prev * x - acc) == 0
+            vec![s_prod * (acc_prev * x - acc)]
+        });
+
+        // return the configuration
+
+        FieldConfig {
+            advice,
+            instance: instance.to_vec(),
+            constant,
+            mode,
+            s_op,
+            s_sub,
+            s_square,
+            s_mul_add,
+            s_is_zero,
+            s_bool,
+            s_select,
+            s_add_const,
+            s_mul_const,
+            s_div,
+            s_inv,
+            s_acc,
+            s_bits,
+            s_cube,
+            s_neg,
+            s_double,
+            s_const,
+            s_lin,
+            s_sqrt,
+            s_in_set,
+            s_prod,
+        }
+    }
+}
+
+impl<F: Field, const W: usize> FieldChip<F, W> {
+    // name the next region `name[i]`, where `i` counts the regions laid out so far, so that
+    // regions laid out by the same instruction are told apart
+    fn region_name(&self, name: &str) -> String {
+        let index = self.regions.fetch_add(1, Ordering::Relaxed);
+        format!("{}[{}]", name, index)
+    }
+
+    // compute `mode * (a * b) + (1 - mode) * (a + b)` through the combined gate and load into
+    // the circuit. The gate only accepts a `mode` of 0 or 1, which is what `add` and `mul` use.
+    pub fn arith(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Number<F>,
+        b: Number<F>,
+        mode: F,
+    ) -> Result<Number<F>, Error> {
+        self.op(layouter, &self.region_name("arith"), a, b, mode)
+    }
+
+    fn op(
+        &self,
+        mut layouter: impl Layouter<F>,
+        name: &str,
+        a: Number<F>,
+        b: Number<F>,
+        mode: F,
+    ) -> Result<Number<F>, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || name,
+            |mut region| {
+                // enable the selector in the region at offset 0. This will enable the selector
+                // for cells at offsets 0 and 1 in this case.
+                config.s_op.enable(&mut region, 0)?;
+                region.assign_fixed(|| "mode", config.mode, 0, || Value::known(mode))?;
+
+                // copy the advice values into the region
+                a.0.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+                b.0.copy_advice(|| "rhs", &mut region, config.advice[1], 0)?;
+
+                // out
+                let lhs = a.0.value().copied();
+                let rhs = b.0.value().copied();
+                let value =
+                    Value::known(mode) * lhs * rhs + Value::known(F::ONE - mode) * (lhs + rhs);
+                // assign `out` to advice column 0 at offset 1
+                region
+                    .assign_advice(|| "out", config.advice[0], 1, || value)
+                    .map(Number)
+            },
+        )
+    }
+
+    // assign `value` to advice and `k` to the fixed column, constrained equal by the `const`
+    // gate. `load_constant_gated` passes `k` itself; any other value fails the gate.
+    pub fn assign_constant_gated(
+        &self,
+        mut layouter: impl Layouter<F>,
+        k: F,
+        value: Value<F>,
+    ) -> Result<Number<F>, Error> {
+        let config = self.config();
+
+        let name = self.region_name("load constant gated");
+
+        layouter.assign_region(
+            || name.as_str(),
+            |mut region| {
+                config.s_const.enable(&mut region, 0)?;
+
+                region.assign_fixed(|| "k", config.constant, 0, || Value::known(k))?;
+                region
+                    .assign_advice(|| "constant", config.advice[0], 0, || value)
+                    .map(Number)
+            },
+        )
+    }
+
+    // load the given bits, least significant first, and constrain each to be boolean and their
+    // weighted sum to equal `x`. The region has `bits.len() + 1` rows: the accumulator starts at
+    // 0 and doubles before adding each bit, most significant first.
+    pub fn assign_bits(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: Number<F>,
+        bits: &[Value<F>],
+    ) -> Result<Vec<Number<F>>, Error> {
+        let config = self.config();
+
+        let name = self.region_name("decompose_bits");
+
+        layouter.assign_region(
+            || name.as_str(),
+            |mut region| {
+                let mut acc =
+                    region.assign_advice_from_constant(|| "acc 0", config.advice[1], 0, F::ZERO)?;
+
+                let mut cells = Vec::with_capacity(bits.len());
+                for (i, &bit) in bits.iter().enumerate().rev() {
+                    let offset = bits.len() - i;
+                    config.s_bool.enable(&mut region, offset)?;
+                    config.s_bits.enable(&mut region, offset)?;
+
+                    let cell = region.assign_advice(
+                        || format!("bit {}", i),
+                        config.advice[0],
+                        offset,
+                        || bit,
+                    )?;
+                    cells.push(Number(cell));
+
+                    let value = acc.value().map(|acc| acc.double()) + bit;
+                    acc = region.assign_advice(
+                        || format!("acc {}", offset),
+                        config.advice[1],
+                        offset,
+                        || value,
+                    )?;
+                }
+
+                // the recomposed value must be `x`
+                region.constrain_equal(acc.cell(), x.0.cell())?;
+
+                cells.reverse();
+                Ok(cells)
+            },
+        )
+    }
+}
+
+// implement the instructions for the chip
+
+#[derive(Clone)]
+pub struct Number<F: Field>(AssignedCell<F, F>);
+
+impl<F: Field> Number<F> {
+    // the assigned value, if it is known
+    pub fn value(&self) -> Value<&F> {
+        self.0.value()
+    }
+
+    // log the assigned value, if it is known, in the current span
+    #[cfg(feature = "tracing")]
+    fn trace(&self) {
+        self.0
+            .value()
+            .map(|value| tracing::debug!(?value, "assigned"));
+    }
+}
+
+// how `fold` combines the values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FoldOp {
+    Sum,
+    Product,
+}
+
+// an operation of the stack machine evaluated by `compute`
+#[derive(Debug, Clone, Copy)]
+pub enum Op<F: Field> {
+    // push a private input
+    Private(Value<F>),
+    // push a constant
+    Const(F),
+    // push a copy of the top value
+    Dup,
+    // replace the top value with its square
+    Square,
+    // pop `b` then `a`, and push `a + b`, `a - b` or `a * b`
+    Add,
+    Sub,
+    Mul,
+}
+
+impl<F: Field> Op<F> {
+    // the same op with any private input replaced by `Value::unknown()`
+    pub fn without_witness(&self) -> Self {
+        match self {
+            Op::Private(_) => Op::Private(Value::unknown()),
+            op => *op,
+        }
+    }
+}
+
+impl<F: Field> fmt::Debug for Number<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut value = None;
+        self.0.value().map(|v| value = Some(*v));
+
+        match value {
+            Some(value) => f.debug_tuple("Number").field(&value).finish(),
+            None => f
+                .debug_tuple("Number")
+                .field(&format_args!("unknown"))
+                .finish(),
+        }
+    }
+}
+
+impl<F: Field, const W: usize> Instructions<F> for FieldChip<F, W> {
+    type Num = Number<F>;
+
+    // load a number as private input into the circuit
+    fn load_private(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let name = self.region_name("load private");
+
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("load_private", region = %name).entered();
+
+        let num = layouter.assign_region(
+            || name.as_str(),
+            |mut region| {
+                region
+                    .assign_advice(|| "private input", config.advice[0], 0, || value)
+                    .map(Number)
+            },
+        )?;
+
+        #[cfg(feature = "tracing")]
+        num.trace();
+
+        Ok(num)
+    }
+
+    // load a private input given as its little-endian bytes, one byte per 8 bits of the field
+    // modulus. The bytes are recomposed as `sum_i bytes[i] * 256^i` in the field, so values
+    // beyond the modulus are reduced. Bytes of the wrong length are rejected.
+    fn load_private_bytes(
+        &self,
+        layouter: impl Layouter<F>,
+        bytes: &[u8],
+    ) -> Result<Self::Num, Error>
+    where
+        F: PrimeField,
+    {
+        if bytes.len() != (F::NUM_BITS as usize).div_ceil(8) {
+            return Err(Error::Synthesis);
+        }
+
+        let value = bytes.iter().rev().fold(F::ZERO, |acc, &byte| {
+            acc * F::from(256) + F::from(byte as u64)
+        });
+
+        self.load_private(layouter, Value::known(value))
+    }
+
+    // load the numbers as private inputs into consecutive rows of a single region
+    fn load_private_many(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: &[Value<F>],
+    ) -> Result<Vec<Self::Num>, Error> {
+        let config = self.config();
+
+        let name = self.region_name("load private many");
+
+        layouter.assign_region(
+            || name.as_str(),
+            |mut region| {
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(offset, &value)| {
+                        region
+                            .assign_advice(
+                                || format!("private input {}", offset),
+                                config.advice[0],
+                                offset,
+                                || value,
+                            )
+                            .map(Number)
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    // load a constant as a private input into the circuit
+    fn load_constant(
+        &self,
+        mut layouter: impl Layouter<F>,
+        constant: F,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let name = self.region_name("load constant");
+
+        layouter.assign_region(
+            || name.as_str(),
+            |mut region| {
+                region
+                    .assign_advice_from_constant(|| "constant", config.advice[0], 0, constant)
+                    .map(Number)
+            },
+        )
+    }
+
+    // load the constants as private inputs into consecutive rows of a single region. Each cell is
+    // still constrained to its constant in the fixed column.
+    fn load_constants(
+        &self,
+        mut layouter: impl Layouter<F>,
+        constants: &[F],
+    ) -> Result<Vec<Self::Num>, Error> {
+        let config = self.config();
+
+        let name = self.region_name("load constants");
+
+        layouter.assign_region(
+            || name.as_str(),
+            |mut region| {
+                constants
+                    .iter()
+                    .enumerate()
+                    .map(|(offset, &constant)| {
+                        region
+                            .assign_advice_from_constant(
+                                || format!("constant {}", offset),
+                                config.advice[0],
+                                offset,
+                                constant,
+                            )
+                            .map(Number)
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    // load the constant 0, the additive identity, into the circuit
+    fn load_zero(&self, layouter: impl Layouter<F>) -> Result<Self::Num, Error> {
+        self.load_constant(layouter, F::ZERO)
+    }
+
+    // load the constant 1, the multiplicative identity, into the circuit
+    fn load_one(&self, layouter: impl Layouter<F>) -> Result<Self::Num, Error> {
+        self.load_constant(layouter, F::ONE)
+    }
+
+    // load the public input at `row` of the instance column into the circuit
+    fn load_public(&self, mut layouter: impl Layouter<F>, row: usize) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let name = self.region_name("load public");
+
+        layouter.assign_region(
+            || name.as_str(),
+            |mut region| {
+                region
+                    .assign_advice_from_instance(
+                        || "public input",
+                        config.instance[0],
+                        row,
+                        config.advice[0],
+                        0,
+                    )
+                    .map(Number)
+            },
+        )
+    }
+
+    // add the values and load into the circuit
+    fn add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        self.op(layouter, &self.region_name("add"), a, b, F::ZERO)
+    }
+
+    // subtract the values and load into the circuit
+    fn sub(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let name = self.region_name("sub");
+
+        layouter.assign_region(
+            || name.as_str(),
+            |mut region| {
+                config.s_sub.enable(&mut region, 0)?;
+
+                // copy the advice values into the region
+                a.0.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+                b.0.copy_advice(|| "rhs", &mut region, config.advice[1], 0)?;
+
+                // out
+                let value = a.0.value().copied() - b.0.value();
+                // assign `out` to advice column 0 at offset 1
+                region
+                    .assign_advice(|| "lhs - rhs", config.advice[0], 1, || value)
+                    .map(Number)
+            },
+        )
+    }
+
+    // multiply the values and load into the circuit
+    fn mul(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        // the indexed name also names the namespace, so that many `mul`s are told apart in the
+        // graph of the layout
+        let name = self.region_name("mul");
+
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("mul", region = %name).entered();
+
+        let num = self.op(layouter.namespace(|| name.as_str()), &name, a, b, F::ONE)?;
+
+        #[cfg(feature = "tracing")]
+        num.trace();
+
+        Ok(num)
+    }
+
+    // square the value and load into the circuit. Unlike `mul(x, x)` this only copies `x`
+    // into the region once.
+    fn square(&self, mut layouter: impl Layouter<F>, x: Self::Num) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let name = self.region_name("square");
+
+        layouter.assign_region(
+            || name.as_str(),
+            |mut region| {
+                config.s_square.enable(&mut region, 0)?;
+
+                // copy the advice value into the region
+                x.0.copy_advice(|| "x", &mut region, config.advice[0], 0)?;
+
+                // out
+                let value = x.0.value().map(|x| x.square());
+                // assign `out` to advice column 0 at offset 1
+                region
+                    .assign_advice(|| "x * x", config.advice[0], 1, || value)
+                    .map(Number)
+            },
+        )
+    }
+
+    // cube the value and load into the circuit, witnessing `x * x` in an intermediate cell of
+    // the same region
+    fn cube(&self, mut layouter: impl Layouter<F>, x: Self::Num) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let name = self.region_name("cube");
+
+        layouter.assign_region(
+            || name.as_str(),
+            |mut region| {
+                config.s_cube.enable(&mut region, 0)?;
+
+                // copy the advice value into the region
+                x.0.copy_advice(|| "x", &mut region, config.advice[0], 0)?;
+
+                // t
+                let t = x.0.value().map(|x| x.square());
+                region.assign_advice(|| "x * x", config.advice[1], 0, || t)?;
+
+                // out
+                let value = t * x.0.value();
+                // assign `out` to advice column 0 at offset 1
+                region
+                    .assign_advice(|| "x * x * x", config.advice[0], 1, || value)
+                    .map(Number)
+            },
+        )
+    }
+
+    // compute `a * b + c` in a single region and load into the circuit
+    fn mul_add(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+        c: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let name = self.region_name("mul_add");
+
+        layouter.assign_region(
+            || name.as_str(),
+            |mut region| {
+                config.s_mul_add.enable(&mut region, 0)?;
+
+                // copy the advice values into the region
+                a.0.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                b.0.copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+                c.0.copy_advice(|| "c", &mut region, config.advice[1], 1)?;
+
+                // out
+                let value = a.0.value().copied() * b.0.value() + c.0.value();
+                // assign `out` to advice column 0 at offset 1
+                region
+                    .assign_advice(|| "a * b + c", config.advice[0], 1, || value)
+                    .map(Number)
+            },
+        )
+    }
+
+    // load 1 into the circuit if the value is zero, and 0 otherwise
+    fn is_zero(&self, mut layouter: impl Layouter<F>, x: Self::Num) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let name = self.region_name("is_zero");
+
+        layouter.assign_region(
+            || name.as_str(),
+            |mut region| {
+                config.s_is_zero.enable(&mut region, 0)?;
+
+                // copy the advice value into the region
+                x.0.copy_advice(|| "x", &mut region, config.advice[0], 0)?;
+
+                // witness the inverse of x, or 0 if there isn't one
+                let inv = x.0.value().map(|x| x.invert().unwrap_or(F::ZERO));
+                region.assign_advice(|| "inv", config.advice[1], 0, || inv)?;
+
+                // out
+                let value =
+                    x.0.value()
+                        .map(|x| if x.is_zero_vartime() { F::ONE } else { F::ZERO });
+                // assign `out` to advice column 0 at offset 1
+                region
+                    .assign_advice(|| "x == 0", config.advice[0], 1, || value)
+                    .map(Number)
+            },
+        )
+    }
+
+    // constrain the value to be either 0 or 1
+    fn assert_bit(&self, mut layouter: impl Layouter<F>, x: Self::Num) -> Result<(), Error> {
+        let config = self.config();
+
+        let name = self.region_name("assert_bit");
+
+        layouter.assign_region(
+            || name.as_str(),
+            |mut region| {
+                config.s_bool.enable(&mut region, 0)?;
+
+                // copy the advice value into the region
+                x.0.copy_advice(|| "x", &mut region, config.advice[0], 0)?;
+
+                Ok(())
+            },
+        )
+    }
+
+    // load `a` into the circuit if `cond` is 1, and `b` if it is 0
+    fn select(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cond: Self::Num,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let name = self.region_name("select");
+
+        layouter.assign_region(
+            || name.as_str(),
+            |mut region| {
+                config.s_select.enable(&mut region, 0)?;
+                // `cond` sits in advice column 0 at offset 0, which is where the boolean gate
+                // looks, so we can constrain it to be a bit in the same region
+                config.s_bool.enable(&mut region, 0)?;
+
+                // copy the advice values into the region
+                cond.0
+                    .copy_advice(|| "cond", &mut region, config.advice[0], 0)?;
+                a.0.copy_advice(|| "a", &mut region, config.advice[1], 0)?;
+                b.0.copy_advice(|| "b", &mut region, config.advice[0], 1)?;
+
+                // out
+                let value =
+                    cond.0.value().copied() * (a.0.value().copied() - b.0.value()) + b.0.value();
+                // assign `out` to advice column 1 at offset 1
+                region
+                    .assign_advice(|| "cond ? a : b", config.advice[1], 1, || value)
+                    .map(Number)
+            },
+        )
+    }
+
+    // constrain the two values to be equal
+    fn assert_equal(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<(), Error> {
+        let config = self.config();
+
+        let name = self.region_name("assert_equal");
+
+        layouter.assign_region(
+            || name.as_str(),
+            |mut region| {
+                // copy the advice values into the region
+                let lhs =
+                    a.0.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+                let rhs =
+                    b.0.copy_advice(|| "rhs", &mut region, config.advice[1], 0)?;
+
+                region.constrain_equal(lhs.cell(), rhs.cell())
+            },
+        )
+    }
+
+    // add a constant held in the fixed column to the value and load into the circuit
+    fn add_constant(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: Self::Num,
+        constant: F,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let name = self.region_name("add_constant");
+
+        layouter.assign_region(
+            || name.as_str(),
+            |mut region| {
+                config.s_add_const.enable(&mut region, 0)?;
+
+                // copy the advice value into the region, and place the constant alongside it
+                x.0.copy_advice(|| "x", &mut region, config.advice[0], 0)?;
+                region.assign_fixed(|| "k", config.constant, 0, || Value::known(constant))?;
+
+                // out
+                let value = x.0.value().map(|x| *x + constant);
+                // assign `out` to advice column 0 at offset 1
+                region
+                    .assign_advice(|| "x + k", config.advice[0], 1, || value)
+                    .map(Number)
+            },
+        )
+    }
+
+    // scale the value by a constant held in the fixed column and load into the circuit
+    fn mul_constant(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: Self::Num,
+        constant: F,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let name = self.region_name("mul_constant");
+
+        layouter.assign_region(
+            || name.as_str(),
+            |mut region| {
+                config.s_mul_const.enable(&mut region, 0)?;
+
+                // copy the advice value into the region, and place the constant alongside it
+                x.0.copy_advice(|| "x", &mut region, config.advice[0], 0)?;
+                region.assign_fixed(|| "k", config.constant, 0, || Value::known(constant))?;
+
+                // out
+                let value = x.0.value().map(|x| constant * x);
+                // assign `out` to advice column 0 at offset 1
+                region
+                    .assign_advice(|| "k * x", config.advice[0], 1, || value)
+                    .map(Number)
+            },
+        )
+    }
+
+    // compute `sum_i xs[i] * ys[i]` through repeated `mul_add` and load into the circuit
+    fn inner_product(
+        &self,
+        mut layouter: impl Layouter<F>,
+        xs: &[Self::Num],
+        ys: &[Self::Num],
+    ) -> Result<Self::Num, CircuitError> {
+        if xs.len() != ys.len() {
+            return Err(CircuitError::LengthMismatch {
+                expected: xs.len(),
+                got: ys.len(),
+            });
+        }
+
+        let mut terms = xs.iter().zip(ys);
+        let (x, y) = match terms.next() {
+            Some(term) => term,
+            None => return Ok(self.load_zero(layouter.namespace(|| "zero"))?),
+        };
+
+        let first = self.mul(layouter.namespace(|| "term 0"), x.clone(), y.clone())?;
+        let out = terms.enumerate().try_fold(first, |acc, (i, (x, y))| {
+            self.mul_add(
+                layouter.namespace(|| format!("term {}", i + 1)),
+                x.clone(),
+                y.clone(),
+                acc,
+            )
+        })?;
+
+        Ok(out)
+    }
+
+    // raise the value to a constant power by square-and-multiply and load into the circuit.
+    // For an exponent of bit length `n` with `w` bits set, this uses `n - 1` square regions and
+    // `w - 1` mul regions. `x^0` is loaded as the constant 1.
+    fn pow_const(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: Self::Num,
+        exp: u64,
+    ) -> Result<Self::Num, Error> {
+        if exp == 0 {
+            return self.load_one(layouter.namespace(|| "one"));
+        }
+
+        // walk the bits of the exponent from the most significant one down
+        let bits = u64::BITS - exp.leading_zeros();
+        (0..bits - 1).rev().try_fold(x.clone(), |acc, i| {
+            let acc = self.square(layouter.namespace(|| format!("square {}", i)), acc)?;
+            if (exp >> i) & 1 == 1 {
+                self.mul(layouter.namespace(|| format!("mul {}", i)), acc, x.clone())
+            } else {
+                Ok(acc)
+            }
+        })
+    }
+
+    // divide the values and load the quotient into the circuit. Dividing by zero is rejected
+    // while assigning the witness.
+    fn div(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let name = self.region_name("div");
+
+        layouter.assign_region(
+            || name.as_str(),
+            |mut region| {
+                config.s_div.enable(&mut region, 0)?;
+
+                // copy the advice values into the region
+                a.0.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                b.0.copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+
+                // q
+                let mut is_zero = false;
+                let b_inv = b.0.value().map(|b| {
+                    is_zero = b.is_zero_vartime();
+                    b.invert().unwrap_or(F::ZERO)
+                });
+                if is_zero {
+                    return Err(Error::Synthesis);
+                }
+                let value = a.0.value().copied() * b_inv;
+                // assign `b_inv` to advice column 1 at offset 1, proving that `b` is nonzero
+                region.assign_advice(|| "1 / b", config.advice[1], 1, || b_inv)?;
+                // assign `q` to advice column 0 at offset 1
+                region
+                    .assign_advice(|| "a / b", config.advice[0], 1, || value)
+                    .map(Number)
+            },
+        )
+    }
+
+    // invert the value and load into the circuit. Zero has no inverse, so it is rejected while
+    // assigning the witness.
+    fn inv(&self, mut layouter: impl Layouter<F>, x: Self::Num) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let name = self.region_name("inv");
+
+        layouter.assign_region(
+            || name.as_str(),
+            |mut region| {
+                config.s_inv.enable(&mut region, 0)?;
+
+                // copy the advice value into the region
+                x.0.copy_advice(|| "x", &mut region, config.advice[0], 0)?;
+
+                // inv
+                let mut is_zero = false;
+                let value = x.0.value().map(|x| {
+                    is_zero = x.is_zero_vartime();
+                    x.invert().unwrap_or(F::ZERO)
+                });
+                if is_zero {
+                    return Err(Error::Synthesis);
+                }
+                // assign `inv` to advice column 0 at offset 1
+                region
+                    .assign_advice(|| "1 / x", config.advice[0], 1, || value)
+                    .map(Number)
+            },
+        )
+    }
+
+    // add up the values through chained `add`s, which takes `xs.len() - 1` regions, and load
+    // the sum into the circuit. The empty sum is loaded as the constant 0.
+    fn sum(&self, layouter: impl Layouter<F>, xs: &[Self::Num]) -> Result<Self::Num, Error> {
+        self.fold(layouter, xs, FoldOp::Sum)
+    }
+
+    // assign the values down advice column 0 and accumulate their sum down advice column 1, all
+    // in a single region of `xs.len() + 1` rows, and load the total into the circuit
+    fn running_sum(
+        &self,
+        mut layouter: impl Layouter<F>,
+        xs: &[Value<F>],
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let name = self.region_name("running_sum");
+
+        layouter.assign_region(
+            || name.as_str(),
+            |mut region| {
+                // the accumulator starts at 0
+                let mut acc =
+                    region.assign_advice_from_constant(|| "acc 0", config.advice[1], 0, F::ZERO)?;
+
+                for (i, &x) in xs.iter().enumerate() {
+                    let offset = i + 1;
+                    config.s_acc.enable(&mut region, offset)?;
+
+                    region.assign_advice(|| format!("x {}", i), config.advice[0], offset, || x)?;
+
+                    let value = acc.value().copied() + x;
+                    acc = region.assign_advice(
+                        || format!("acc {}", offset),
+                        config.advice[1],
+                        offset,
+                        || value,
+                    )?;
+                }
+
+                Ok(Number(acc))
+            },
+        )
+    }
+
+    // load the values down a single region of `xs.len() + 1` rows, multiplying them into an
+    // accumulator alongside, and return the final product. The empty product is 1.
+    fn running_product(
+        &self,
+        mut layouter: impl Layouter<F>,
+        xs: &[Value<F>],
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let name = self.region_name("running_product");
+
+        layouter.assign_region(
+            || name.as_str(),
+            |mut region| {
+                // the accumulator starts at 1
+                let mut acc =
+                    region.assign_advice_from_constant(|| "acc 0", config.advice[1], 0, F::ONE)?;
+
+                for (i, &x) in xs.iter().enumerate() {
+                    let offset = i + 1;
+                    config.s_prod.enable(&mut region, offset)?;
 
-    fn load_private(&self, layouter: impl Layouter<F>, value: Value<F>)
-        -> Result<Self::Num, Error>;
+                    region.assign_advice(|| format!("x {}", i), config.advice[0], offset, || x)?;
 
-    fn load_constant(&self, layouter: impl Layouter<F>, constant: F) -> Result<Self::Num, Error>;
+                    let value = acc.value().copied() * x;
+                    acc = region.assign_advice(
+                        || format!("acc {}", offset),
+                        config.advice[1],
+                        offset,
+                        || value,
+                    )?;
+                }
 
-    fn mul(
-        &self,
-        layouter: impl Layouter<F>,
-        a: Self::Num,
-        b: Self::Num,
-    ) -> Result<Self::Num, Error>;
+                Ok(Number(acc))
+            },
+        )
+    }
 
-    fn expose_public(
+    // decompose the value into `n` bits, least significant first, and load them into the circuit.
+    // `n` must be below the capacity of the field, so the recomposed sum can't wrap.
+    fn decompose_bits(
         &self,
         layouter: impl Layouter<F>,
-        num: Self::Num,
-        row: usize,
-    ) -> Result<(), Error>;
-}
+        x: Self::Num,
+        n: usize,
+    ) -> Result<Vec<Self::Num>, Error>
+    where
+        F: PrimeField,
+    {
+        if n >= F::CAPACITY as usize {
+            return Err(Error::Synthesis);
+        }
 
-#[derive(Debug, Clone)]
-pub struct FieldConfig {
-    advice: [Column<Advice>; 2],
-    instance: Column<Instance>,
-    s_mul: Selector,
-}
+        // this relies on the little-endian representation of the pasta fields
+        let all_bits = x.value().map(|x| {
+            let repr = x.to_repr();
+            (0..F::NUM_BITS as usize)
+                .map(|i| {
+                    let byte = repr.as_ref()[i / 8];
+                    if (byte >> (i % 8)) & 1 == 1 {
+                        F::ONE
+                    } else {
+                        F::ZERO
+                    }
+                })
+                .collect::<Vec<_>>()
+        });
 
-struct FieldChip<F> {
-    config: FieldConfig,
-    _marker: PhantomData<F>,
-}
+        // all the bits must recompose to `x`, or the representation isn't little-endian
+        #[cfg(debug_assertions)]
+        x.value()
+            .zip(all_bits.as_ref())
+            .assert_if_known(|(x, bits)| {
+                let recomposed = bits
+                    .iter()
+                    .rev()
+                    .fold(F::ZERO, |acc, bit| acc.double() + bit);
+                **x == recomposed
+            });
 
-impl<F: Field> Chip<F> for FieldChip<F> {
-    type Config = FieldConfig;
-    type Loaded = ();
+        let bits = (0..n)
+            .map(|i| all_bits.as_ref().map(|bits| bits[i]))
+            .collect::<Vec<_>>();
 
-    fn config(&self) -> &Self::Config {
-        &self.config
+        self.assign_bits(layouter, x, &bits)
     }
 
-    fn loaded(&self) -> &Self::Loaded {
-        &()
+    // load 1 into the circuit if the value equals the constant, and 0 otherwise. The constant is
+    // subtracted through the fixed column, then the difference is tested against zero.
+    fn is_equal_constant(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: Self::Num,
+        k: F,
+    ) -> Result<Self::Num, Error> {
+        let diff = self.add_constant(layouter.namespace(|| "x - k"), x, -k)?;
+
+        self.is_zero(layouter.namespace(|| "x - k == 0"), diff)
     }
-}
 
-impl<F: Field> FieldChip<F> {
-    fn construct(config: <Self as Chip<F>>::Config) -> Self {
-        Self {
-            config,
-            _marker: PhantomData,
-        }
+    // negate the value and load into the circuit
+    fn neg(&self, mut layouter: impl Layouter<F>, x: Self::Num) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        let name = self.region_name("neg");
+
+        layouter.assign_region(
+            || name.as_str(),
+            |mut region| {
+                config.s_neg.enable(&mut region, 0)?;
+
+                // copy the advice value into the region
+                x.0.copy_advice(|| "x", &mut region, config.advice[0], 0)?;
+
+                // out
+                let value = x.0.value().map(|x| -*x);
+                // assign `out` to advice column 0 at offset 1
+                region
+                    .assign_advice(|| "-x", config.advice[0], 1, || value)
+                    .map(Number)
+            },
+        )
     }
 
-    fn configure(
-        meta: &mut ConstraintSystem<F>,
-        advice: [Column<Advice>; 2],
-        instance: Column<Instance>,
-        constant: Column<Fixed>,
-    ) -> <Self as Chip<F>>::Config {
-        meta.enable_equality(instance);
-        meta.enable_equality(constant);
-        for column in &advice {
-            meta.enable_equality(*column);
-        }
+    // double the value and load into the circuit. Unlike `add(x, x)` this only copies `x` into
+    // the region once.
+    fn double(&self, mut layouter: impl Layouter<F>, x: Self::Num) -> Result<Self::Num, Error> {
+        let config = self.config();
 
-        let s_mul = meta.selector();
+        let name = self.region_name("double");
 
-        // create the multiplication gate
-        meta.create_gate("mul", |meta| {
-            // a9 | a1 | s_mul
-            //----------------
-            // lhs | rhs | s_mul
-            // out
-            let lhs = meta.query_advice(advice[0], Rotation::cur());
-            let rhs = meta.query_advice(advice[1], Rotation::cur());
-            let out = meta.query_advice(advice[0], Rotation::next());
-            let s_mul = meta.query_selector(s_mul);
+        layouter.assign_region(
+            || name.as_str(),
+            |mut region| {
+                config.s_double.enable(&mut region, 0)?;
 
-            // the polynomial is: s_mul * (lhs * rhs - out) == 0
-            vec![s_mul * (lhs * rhs - out)]
-        });
+                // copy the advice value into the region
+                x.0.copy_advice(|| "x", &mut region, config.advice[0], 0)?;
 
-        // return the configuration
+                // out
+                let value = x.0.value().map(|x| x.double());
+                // assign `out` to advice column 0 at offset 1
+                region
+                    .assign_advice(|| "x + x", config.advice[0], 1, || value)
+                    .map(Number)
+            },
+        )
+    }
 
-        FieldConfig {
-            advice,
-            instance,
-            s_mul,
+    // constrain the value to be nonzero by witnessing its inverse through the `inv` gate, which
+    // can't be satisfied for 0
+    fn assert_nonzero(&self, layouter: impl Layouter<F>, x: Self::Num) -> Result<(), Error> {
+        self.inv(layouter, x).map(|_| ())
+    }
+
+    // evaluate the ops over a stack and load the single value left on it. Ops that find too few
+    // values on the stack, or leaving other than one value, fail with `Error::Synthesis`.
+    fn compute(&self, mut layouter: impl Layouter<F>, ops: &[Op<F>]) -> Result<Self::Num, Error> {
+        let mut stack: Vec<Self::Num> = vec![];
+
+        for op in ops {
+            let num = match op {
+                Op::Private(value) => {
+                    self.load_private(layouter.namespace(|| "private"), *value)?
+                }
+                Op::Const(constant) => {
+                    self.load_constant(layouter.namespace(|| "const"), *constant)?
+                }
+                Op::Dup => stack.last().cloned().ok_or(Error::Synthesis)?,
+                Op::Square => {
+                    let x = stack.pop().ok_or(Error::Synthesis)?;
+                    self.square(layouter.namespace(|| "square"), x)?
+                }
+                Op::Add | Op::Sub | Op::Mul => {
+                    let b = stack.pop().ok_or(Error::Synthesis)?;
+                    let a = stack.pop().ok_or(Error::Synthesis)?;
+                    match op {
+                        Op::Add => self.add(layouter.namespace(|| "add"), a, b)?,
+                        Op::Sub => self.sub(layouter.namespace(|| "sub"), a, b)?,
+                        _ => self.mul(layouter.namespace(|| "mul"), a, b)?,
+                    }
+                }
+            };
+            stack.push(num);
+        }
+
+        match (stack.pop(), stack.is_empty()) {
+            (Some(num), true) => Ok(num),
+            _ => Err(Error::Synthesis),
         }
     }
-}
 
-// implement the instructions for the chip
+    // combine the values with `add` or `mul` and load into the circuit. The empty slice folds to
+    // the identity of the operation, loaded as a constant.
+    fn fold(
+        &self,
+        mut layouter: impl Layouter<F>,
+        xs: &[Self::Num],
+        op: FoldOp,
+    ) -> Result<Self::Num, Error> {
+        let (name, mode, identity) = match op {
+            FoldOp::Sum => ("add", F::ZERO, F::ZERO),
+            FoldOp::Product => ("mul", F::ONE, F::ONE),
+        };
 
-#[derive(Clone)]
-struct Number<F: Field>(AssignedCell<F, F>);
+        let (first, rest) = match xs.split_first() {
+            Some(split) => split,
+            None => return self.load_constant(layouter.namespace(|| "identity"), identity),
+        };
 
-impl<F: Field> Instructions<F> for FieldChip<F> {
-    type Num = Number<F>;
+        rest.iter()
+            .enumerate()
+            .try_fold(first.clone(), |acc, (i, x)| {
+                let layouter = layouter.namespace(|| format!("{} {}", name, i));
+                self.op(layouter, &self.region_name(name), acc, x.clone(), mode)
+            })
+    }
 
-    // load a number as private input into the circuit
-    fn load_private(
+    // multiply the value by 2^k by doubling it `k` times, and load into the circuit. A shift by
+    // 0 returns `x` itself.
+    fn shift_left(
         &self,
         mut layouter: impl Layouter<F>,
-        value: Value<F>,
+        x: Self::Num,
+        k: u32,
     ) -> Result<Self::Num, Error> {
+        (0..k).try_fold(x, |acc, i| {
+            self.double(layouter.namespace(|| format!("double {}", i)), acc)
+        })
+    }
+
+    // load a constant as a private input into the circuit, bound to the fixed column by a gate
+    // rather than by an equality constraint
+    fn load_constant_gated(&self, layouter: impl Layouter<F>, k: F) -> Result<Self::Num, Error> {
+        self.assign_constant_gated(layouter, k, Value::known(k))
+    }
+
+    // constrain `sum_i coeff_i * num_i == rhs`. The terms are accumulated down a single region of
+    // `terms.len() + 1` rows, with the coefficients in the fixed column, and the final
+    // accumulator is constrained to the constant `rhs`.
+    fn assert_linear_combo(
+        &self,
+        mut layouter: impl Layouter<F>,
+        terms: &[(F, Self::Num)],
+        rhs: F,
+    ) -> Result<(), Error> {
         let config = self.config();
 
+        let name = self.region_name("assert_linear_combo");
+
         layouter.assign_region(
-            || "load private",
+            || name.as_str(),
             |mut region| {
-                region
-                    .assign_advice(|| "private input", config.advice[0], 0, || value)
-                    .map(Number)
+                // the accumulator starts at 0
+                let mut acc =
+                    region.assign_advice_from_constant(|| "acc 0", config.advice[1], 0, F::ZERO)?;
+
+                for (i, (coeff, num)) in terms.iter().enumerate() {
+                    let offset = i + 1;
+                    config.s_lin.enable(&mut region, offset)?;
+
+                    num.0.copy_advice(
+                        || format!("x {}", i),
+                        &mut region,
+                        config.advice[0],
+                        offset,
+                    )?;
+                    region.assign_fixed(
+                        || format!("coeff {}", i),
+                        config.constant,
+                        offset,
+                        || Value::known(*coeff),
+                    )?;
+
+                    let value = acc.value().copied() + num.0.value().map(|x| *coeff * x);
+                    acc = region.assign_advice(
+                        || format!("acc {}", offset),
+                        config.advice[1],
+                        offset,
+                        || value,
+                    )?;
+                }
+
+                region.constrain_constant(acc.cell(), rhs)
             },
         )
     }
 
-    // load a constant as a private input into the circuit
-    fn load_constant(
+    // constrain `x - y == q * modulus` for a witnessed signed quotient `q` in
+    // [-2^(QUOTIENT_BITS - 1), 2^(QUOTIENT_BITS - 1)), i.e. `x` is congruent to `y` modulo
+    // `modulus` whenever `|x - y| < modulus * 2^(QUOTIENT_BITS - 1)`. Bounding `q` through the
+    // bits of `q + 2^(QUOTIENT_BITS - 1)` stops the product from wrapping around the field, where
+    // every `x - y` is a multiple of `modulus`.
+    fn assert_congruent(
         &self,
         mut layouter: impl Layouter<F>,
-        constant: F,
-    ) -> Result<Self::Num, Error> {
+        x: Self::Num,
+        y: Self::Num,
+        modulus: F,
+    ) -> Result<(), Error>
+    where
+        F: PrimeField,
+    {
+        let inv = Option::<F>::from(modulus.invert()).ok_or(Error::Synthesis)?;
+
+        let diff = self.sub(layouter.namespace(|| "x - y"), x, y)?;
+
+        // shift the quotient into [0, 2^QUOTIENT_BITS), so that negative quotients have bits too
+        let offset = (1..Self::QUOTIENT_BITS).fold(F::ONE, |offset, _| offset.double());
+        let shifted = diff.value().map(|diff| *diff * inv + offset);
+        let shifted = self.load_private(layouter.namespace(|| "load q + offset"), shifted)?;
+        self.decompose_bits(
+            layouter.namespace(|| "q + offset bits"),
+            shifted.clone(),
+            Self::QUOTIENT_BITS,
+        )?;
+        let q = self.add_constant(layouter.namespace(|| "q"), shifted, -offset)?;
+
+        let multiple = self.mul_constant(layouter.namespace(|| "q * modulus"), q, modulus)?;
+        self.assert_equal(
+            layouter.namespace(|| "x - y == q * modulus"),
+            diff,
+            multiple,
+        )
+    }
+
+    // load a square root of the value into the circuit. Non-residues have no square root, so
+    // they are rejected while assigning the witness.
+    fn sqrt(&self, mut layouter: impl Layouter<F>, x: Self::Num) -> Result<Self::Num, Error> {
         let config = self.config();
 
+        let name = self.region_name("sqrt");
+
         layouter.assign_region(
-            || "load constant",
+            || name.as_str(),
             |mut region| {
+                config.s_sqrt.enable(&mut region, 0)?;
+
+                // copy the advice value into the region
+                x.0.copy_advice(|| "x", &mut region, config.advice[0], 0)?;
+
+                // r
+                let mut is_residue = true;
+                let value = x.0.value().map(|x| {
+                    let r = x.sqrt();
+                    is_residue = bool::from(r.is_some());
+                    r.unwrap_or(F::ZERO)
+                });
+                if !is_residue {
+                    return Err(Error::Synthesis);
+                }
+                // assign `r` to advice column 0 at offset 1
                 region
-                    .assign_advice_from_constant(|| "constant", config.advice[0], 0, constant)
+                    .assign_advice(|| "sqrt(x)", config.advice[0], 1, || value)
                     .map(Number)
             },
         )
     }
 
-    // multiply the values and load into the circuit
-    fn mul(
+    // compute `sum_i weights[i] * xs[i] / sum_i weights[i]` through `mul_constant`, `sum` and
+    // `div`, and load into the circuit. The weights are fixed constants, so a zero total weight
+    // is rejected while laying out the circuit.
+    fn weighted_mean(
         &self,
         mut layouter: impl Layouter<F>,
-        a: Self::Num,
-        b: Self::Num,
-    ) -> Result<Self::Num, Error> {
+        xs: &[Self::Num],
+        weights: &[F],
+    ) -> Result<Self::Num, CircuitError> {
+        if xs.len() != weights.len() {
+            return Err(CircuitError::LengthMismatch {
+                expected: xs.len(),
+                got: weights.len(),
+            });
+        }
+
+        let total = weights.iter().fold(F::ZERO, |total, w| total + w);
+        if total.is_zero_vartime() {
+            return Err(Error::Synthesis.into());
+        }
+
+        let terms = xs
+            .iter()
+            .zip(weights)
+            .enumerate()
+            .map(|(i, (x, &w))| {
+                self.mul_constant(
+                    layouter.namespace(|| format!("w{} * x{}", i, i)),
+                    x.clone(),
+                    w,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let weighted = self.sum(layouter.namespace(|| "weighted sum"), &terms)?;
+        let total = self.load_constant(layouter.namespace(|| "total weight"), total)?;
+
+        Ok(self.div(layouter.namespace(|| "mean"), weighted, total)?)
+    }
+
+    // constrain the value to be one of `allowed`, which holds between 1 and `MAX_SET_SIZE`
+    // constants. Smaller sets are padded by repeating the first value, which leaves the product
+    // of differences unchanged in whether it vanishes.
+    fn assert_in_set(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: Self::Num,
+        allowed: &[F],
+    ) -> Result<(), Error> {
+        if allowed.is_empty() || allowed.len() > Self::MAX_SET_SIZE {
+            return Err(Error::Synthesis);
+        }
+
         let config = self.config();
+        let padded = (0..Self::MAX_SET_SIZE).map(|i| *allowed.get(i).unwrap_or(&allowed[0]));
+        // the cells of v0, v1 and v2 in the gate
+        let cells = [
+            (config.advice[1], 0),
+            (config.advice[0], 1),
+            (config.advice[1], 1),
+        ];
+
+        let name = self.region_name("in_set");
 
         layouter.assign_region(
-            || "mul",
+            || name.as_str(),
             |mut region| {
-                // enable the selector in the region at offset 0. This will enable the selector
-                // for cells at offsets 0 and 1 in this case.
-                config.s_mul.enable(&mut region, 0)?;
+                config.s_in_set.enable(&mut region, 0)?;
 
-                // copy the advice values into the region
-                a.0.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
-                b.0.copy_advice(|| "rhs", &mut region, config.advice[1], 0)?;
+                // copy the advice value into the region
+                x.0.copy_advice(|| "x", &mut region, config.advice[0], 0)?;
 
-                // out
-                let value = a.0.value().copied() * b.0.value();
-                // assign `out` to advice column 0 at offset 1
-                region
-                    .assign_advice(|| "lhs * rhs", config.advice[1], 0, || value)
-                    .map(Number)
+                // the allowed values are fixed by the constant column
+                for (i, (v, &(column, offset))) in padded.clone().zip(&cells).enumerate() {
+                    region.assign_advice_from_constant(|| format!("v{}", i), column, offset, v)?;
+                }
+
+                Ok(())
             },
         )
     }
 
-    // load the public input into the circuit
+    // constrain `commitment` to open to the message `m` with the randomness `r`, i.e.
+    // `commitment = g * m + h * r`. This is a Pedersen-style commitment over the field itself
+    // rather than an elliptic curve group, so it is only binding for teaching purposes.
+    fn check_commitment(
+        &self,
+        mut layouter: impl Layouter<F>,
+        m: Self::Num,
+        r: Self::Num,
+        g: F,
+        h: F,
+        commitment: Self::Num,
+    ) -> Result<(), Error> {
+        let gm = self.mul_constant(layouter.namespace(|| "g * m"), m, g)?;
+        let hr = self.mul_constant(layouter.namespace(|| "h * r"), r, h)?;
+        let opened = self.add(layouter.namespace(|| "g * m + h * r"), gm, hr)?;
+
+        self.assert_equal(layouter.namespace(|| "opening"), opened, commitment)
+    }
+
+    // multiply the values, and constrain the product to `row` of the first instance column. The
+    // product is returned for further use.
+    fn mul_and_expose(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+        row: usize,
+    ) -> Result<Self::Num, Error> {
+        let out = self.mul(layouter.namespace(|| "a * b"), a, b)?;
+        self.expose_public(layouter.namespace(|| "expose"), out.clone(), 0, row)?;
+
+        Ok(out)
+    }
+
+    // load `x mod 2` into the circuit as the least significant bit of the `n`-bit decomposition
+    // of `x`, which the `bits` gate constrains to be boolean. Like `decompose_bits`, this also
+    // constrains `x` to [0, 2^n), and `n` must be at least 1.
+    fn parity(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: Self::Num,
+        n: usize,
+    ) -> Result<Self::Num, Error>
+    where
+        F: PrimeField,
+    {
+        let bits = self.decompose_bits(layouter.namespace(|| "bits of x"), x, n)?;
+        bits.into_iter().next().ok_or(Error::Synthesis)
+    }
+
+    // load the larger of the values into the circuit. Field elements have no order, so both
+    // values are constrained to [0, 2^n) by decomposing them into `n` bits, and compared as
+    // integers: `a - b + 2^n` then lies in [1, 2^(n + 1)), and its bit `n` is set exactly when
+    // `a >= b`. `n + 1` must be below the capacity of the field, so the sum can't wrap.
+    fn max(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+        n: usize,
+    ) -> Result<Self::Num, Error>
+    where
+        F: PrimeField,
+    {
+        if n + 1 >= F::CAPACITY as usize {
+            return Err(Error::Synthesis);
+        }
+
+        self.decompose_bits(layouter.namespace(|| "bound a"), a.clone(), n)?;
+        self.decompose_bits(layouter.namespace(|| "bound b"), b.clone(), n)?;
+
+        let offset = (0..n).fold(F::ONE, |offset, _| offset.double());
+        let diff = self.sub(layouter.namespace(|| "a - b"), a.clone(), b.clone())?;
+        let diff = self.add_constant(layouter.namespace(|| "a - b + 2^n"), diff, offset)?;
+        let bits = self.decompose_bits(layouter.namespace(|| "compare"), diff, n + 1)?;
+
+        // `ge` is the top bit, i.e. `a >= b`
+        let ge = bits[n].clone();
+        self.select(layouter.namespace(|| "max"), ge, a, b)
+    }
+
+    // constrain the value to equal the constant `k`
+    fn assert_equal_constant(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: Self::Num,
+        k: F,
+    ) -> Result<(), Error> {
+        let k = self.load_constant(layouter.namespace(|| "load k"), k)?;
+        self.assert_equal(layouter.namespace(|| "x == k"), x, k)
+    }
+
+    // constrain `a * b == c * d`, e.g. to check `a / c == d / b` by cross-multiplication
+    // without dividing
+    fn assert_product_equal(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+        c: Self::Num,
+        d: Self::Num,
+    ) -> Result<(), Error> {
+        let ab = self.mul(layouter.namespace(|| "a * b"), a, b)?;
+        let cd = self.mul(layouter.namespace(|| "c * d"), c, d)?;
+
+        self.assert_equal(layouter.namespace(|| "a * b == c * d"), ab, cd)
+    }
+
+    // load the public input into `row` of the instance column at index `column`
     fn expose_public(
         &self,
         mut layouter: impl Layouter<F>,
         num: Self::Num,
+        column: usize,
         row: usize,
     ) -> Result<(), Error> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("expose_public", column, row).entered();
+        #[cfg(feature = "tracing")]
+        num.trace();
+
         let config = self.config();
+        let instance = config.instance.get(column).ok_or(Error::Synthesis)?;
         // constrain equality
-        layouter.constrain_instance(num.0.cell(), config.instance, row)
+        layouter.constrain_instance(num.0.cell(), *instance, row)
+    }
+
+    // constrain any cell, not just a `Number`, to `row` of the instance column at index `column`.
+    // The cell's column must have equality enabled.
+    fn expose_public_cell(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: Cell,
+        column: usize,
+        row: usize,
+    ) -> Result<(), Error> {
+        let config = self.config();
+        let instance = config.instance.get(column).ok_or(Error::Synthesis)?;
+
+        layouter.constrain_instance(cell, *instance, row)
+    }
+
+    // load the public inputs into consecutive rows starting at `start_row`
+    fn expose_public_many(
+        &self,
+        mut layouter: impl Layouter<F>,
+        nums: &[Self::Num],
+        start_row: usize,
+    ) -> Result<(), CircuitError> {
+        let config = self.config();
+
+        for (i, num) in nums.iter().enumerate() {
+            let row = start_row + i;
+            layouter
+                .constrain_instance(num.0.cell(), config.instance[0], row)
+                .map_err(|e| match e {
+                    // the row lies outside the usable rows of the instance column
+                    Error::NotEnoughRowsAvailable { .. } => CircuitError::InvalidRow(row),
+                    e => e.into(),
+                })?;
+        }
+
+        Ok(())
     }
 }
 
-// We specify only the private inputs in the circuit definition
-#[derive(Default)]
-pub struct MyCircuit<F: Field> {
+// We specify only the private inputs in the circuit definition. `P` is the floor planner that
+// lays out the regions.
+pub struct MyCircuit<F: Field, P = SimpleFloorPlanner> {
     constant: F,
     a: Value<F>,
     b: Value<F>,
+    strict: bool,
+    _planner: PhantomData<P>,
+}
+
+impl<F: Field, P> Default for MyCircuit<F, P> {
+    fn default() -> Self {
+        Self::with_floor_planner(F::default(), Value::unknown(), Value::unknown())
+    }
+}
+
+impl<F: Field, P> MyCircuit<F, P> {
+    // like `new`, for a circuit laid out by the floor planner `P`
+    pub fn with_floor_planner(constant: F, a: Value<F>, b: Value<F>) -> Self {
+        Self {
+            constant,
+            a,
+            b,
+            strict: false,
+            _planner: PhantomData,
+        }
+    }
+
+    // make `synthesize` fail when only one of `a` and `b` is known, instead of silently laying
+    // out the unknown value. A circuit with neither known, as laid out for key generation, is
+    // accepted either way.
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    // compute the public input `c = constant * a^2 * b^2` off-circuit, if both witnesses are
+    // known
+    pub fn evaluate(&self) -> Option<F> {
+        let mut c = None;
+        (self.a * self.b).map(|ab| c = Some(self.constant * ab.square()));
+        c
+    }
+
+    // the public output `c` the circuit exposes, e.g. to serialize with
+    // `prove::serialize_public`. This is the same as `evaluate`.
+    pub fn public_output(&self) -> Option<F> {
+        self.evaluate()
+    }
+
+    // the instance column the circuit expects, i.e. `[c]`. It is empty if a witness is unknown.
+    pub fn expected_public(&self) -> Vec<F> {
+        self.evaluate().into_iter().collect()
+    }
 }
 
 impl<F: Field> MyCircuit<F> {
     pub fn new(constant: F, a: Value<F>, b: Value<F>) -> Self {
-        Self { constant, a, b }
+        Self::with_floor_planner(constant, a, b)
+    }
+
+    pub fn builder() -> MyCircuitBuilder<F> {
+        MyCircuitBuilder::default()
+    }
+
+    // the smallest `k` whose 2^k rows fit every region of the circuit along with the rows
+    // reserved for blinding
+    pub fn min_k() -> u32 {
+        let mut meta = ConstraintSystem::default();
+        let config = Self::configure(&mut meta);
+
+        // lay out the circuit without witnesses to count the rows its regions consume
+        let mut counter = rows::RowCounter::default();
+        let constants = vec![config.constant];
+        <Self as Circuit<F>>::FloorPlanner::synthesize(
+            &mut counter,
+            &Self::default(),
+            config,
+            constants,
+        )
+        .expect("laying out MyCircuit can't fail");
+
+        // halo2 leaves the last `blinding_factors() + 1` rows unusable
+        let rows = counter.rows() + meta.blinding_factors() + 1;
+        rows.next_power_of_two().trailing_zeros()
+    }
+}
+
+// Builds a `MyCircuit`, leaving any witness that isn't set as `Value::unknown()`. This is handy
+// for key generation, where the witnesses are absent.
+#[derive(Default)]
+pub struct MyCircuitBuilder<F: Field> {
+    constant: F,
+    a: Value<F>,
+    b: Value<F>,
+}
+
+impl<F: Field> MyCircuitBuilder<F> {
+    pub fn constant(mut self, constant: F) -> Self {
+        self.constant = constant;
+        self
+    }
+
+    pub fn a(mut self, a: Value<F>) -> Self {
+        self.a = a;
+        self
+    }
+
+    pub fn b(mut self, b: Value<F>) -> Self {
+        self.b = b;
+        self
+    }
+
+    pub fn build(self) -> MyCircuit<F> {
+        MyCircuit::new(self.constant, self.a, self.b)
     }
 }
 
-impl<F: Field> Circuit<F> for MyCircuit<F> {
+impl<F: Field, P: FloorPlanner> Circuit<F> for MyCircuit<F, P> {
     type Config = FieldConfig;
-    type FloorPlanner = SimpleFloorPlanner;
+    type FloorPlanner = P;
 
     fn without_witnesses(&self) -> Self {
-        Self::default()
+        // the constant is part of the circuit definition, not a witness
+        Self {
+            constant: self.constant,
+            strict: self.strict,
+            ..Self::default()
+        }
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
@@ -219,7 +2528,7 @@ impl<F: Field> Circuit<F> for MyCircuit<F> {
         // create a fixed column to load constants
         let constant = meta.fixed_column();
 
-        FieldChip::configure(meta, advice, instance, constant)
+        FieldChip::configure(meta, advice, &[instance], constant)
     }
 
     fn synthesize(
@@ -227,6 +2536,14 @@ impl<F: Field> Circuit<F> for MyCircuit<F> {
         config: Self::Config,
         mut layouter: impl Layouter<F>,
     ) -> Result<(), Error> {
+        if self.strict {
+            // `evaluate` needs both witnesses, so a known one is rejected exactly when the other
+            // is missing
+            let incomplete = self.evaluate().is_none();
+            self.a.error_if_known_and(|_| incomplete)?;
+            self.b.error_if_known_and(|_| incomplete)?;
+        }
+
         let field_chip = FieldChip::<F>::construct(config);
 
         // load the private values
@@ -242,10 +2559,16 @@ impl<F: Field> Circuit<F> for MyCircuit<F> {
         // absq = ab * ab
         // c = constant * absq
         let ab = field_chip.mul(layouter.namespace(|| "a * b"), a, b)?;
-        let absq = field_chip.mul(layouter.namespace(|| "ab * ab"), ab.clone(), ab)?;
-        let c = field_chip.mul(layouter.namespace(|| "constant * absq"), constant, absq)?;
+        let absq = field_chip.square(layouter.namespace(|| "ab * ab"), ab)?;
+
+        // and expose the result as a public input to the circuit
+        field_chip.mul_and_expose(
+            layouter.namespace(|| "expose c = constant * absq"),
+            constant,
+            absq,
+            0,
+        )?;
 
-        // expose the result as a public input to the circuit
-        field_chip.expose_public(layouter.namespace(|| "expose c"), c, 0)
+        Ok(())
     }
 }
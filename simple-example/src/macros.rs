@@ -0,0 +1,136 @@
+//! The `circuit!` macro, declaring a circuit from a product of private inputs and constants.
+
+#[doc(hidden)]
+pub use halo2_proofs as __halo2;
+
+// declare a circuit exposing the value of a product expression as the public input at row 0.
+// The private inputs are listed after the name and each is loaded once; integer literals are
+// loaded as constants. Only `*` and parentheses are supported so far:
+//
+//     circuit!(MyMacroCircuit(a, b) = 7 * (a * b) * (a * b));
+//
+// declares `MyMacroCircuit::new(a: Value<F>, b: Value<F>)` implementing `Circuit<F>`.
+#[macro_export]
+macro_rules! circuit {
+    ($name:ident($($field:ident),+ $(,)?) = $($expr:tt)+) => {
+        #[derive(Default)]
+        pub struct $name<F: $crate::macros::__halo2::arithmetic::Field> {
+            $($field: $crate::macros::__halo2::circuit::Value<F>,)+
+        }
+
+        impl<F: $crate::macros::__halo2::arithmetic::Field> $name<F> {
+            #[allow(clippy::too_many_arguments)]
+            pub fn new($($field: $crate::macros::__halo2::circuit::Value<F>),+) -> Self {
+                Self { $($field),+ }
+            }
+        }
+
+        impl<F> $crate::macros::__halo2::plonk::Circuit<F> for $name<F>
+        where
+            F: $crate::macros::__halo2::pasta::group::ff::PrimeField,
+        {
+            type Config = $crate::FieldConfig;
+            type FloorPlanner = $crate::macros::__halo2::circuit::SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                Self::default()
+            }
+
+            fn configure(
+                meta: &mut $crate::macros::__halo2::plonk::ConstraintSystem<F>,
+            ) -> Self::Config {
+                let advice = [meta.advice_column(), meta.advice_column()];
+                let instance = meta.instance_column();
+                let constant = meta.fixed_column();
+
+                $crate::FieldChip::configure(meta, advice, &[instance], constant)
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl $crate::macros::__halo2::circuit::Layouter<F>,
+            ) -> Result<(), $crate::macros::__halo2::plonk::Error> {
+                use $crate::Instructions;
+
+                let chip = $crate::FieldChip::<F>::construct(config);
+
+                $(
+                    let $field = chip.load_private(
+                        layouter.namespace(|| concat!("load ", stringify!($field))),
+                        self.$field,
+                    )?;
+                )+
+
+                let out = $crate::__circuit_expr!(chip, layouter; $($expr)+);
+
+                chip.expose_public(layouter.namespace(|| "expose out"), out, 0, 0)
+            }
+        }
+    };
+}
+
+// lay out a product of factors, multiplying from the right
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __circuit_expr {
+    ($chip:ident, $layouter:ident; $factor:tt) => {
+        $crate::__circuit_factor!($chip, $layouter; $factor)
+    };
+    ($chip:ident, $layouter:ident; $factor:tt * $($rest:tt)+) => {{
+        let lhs = $crate::__circuit_factor!($chip, $layouter; $factor);
+        let rhs = $crate::__circuit_expr!($chip, $layouter; $($rest)+);
+        $chip.mul($layouter.namespace(|| "mul"), lhs, rhs)?
+    }};
+}
+
+// lay out a single factor: a parenthesized product, a private input or a constant
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __circuit_factor {
+    ($chip:ident, $layouter:ident; ($($inner:tt)+)) => {
+        $crate::__circuit_expr!($chip, $layouter; $($inner)+)
+    };
+    ($chip:ident, $layouter:ident; $input:ident) => {
+        $input.clone()
+    };
+    ($chip:ident, $layouter:ident; $constant:literal) => {
+        $chip.load_constant($layouter.namespace(|| "load constant"), F::from($constant))?
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::Value, pasta::Fp};
+
+    use crate::{
+        test_util::{assert_accepts, assert_rejects},
+        MyCircuit,
+    };
+
+    circuit!(MacroCircuit(a, b) = 7 * (a * b) * (a * b));
+
+    #[test]
+    fn circuit_macro() -> Result<(), Box<dyn std::error::Error>> {
+        let k = 4;
+
+        let a = Value::known(Fp::from(2));
+        let b = Value::known(Fp::from(3));
+
+        // the same result as `MyCircuit`
+        let c = MyCircuit::new(Fp::from(7), a, b)
+            .evaluate()
+            .ok_or("the witnesses are known")?;
+        assert_accepts(k, &MyCircuit::new(Fp::from(7), a, b), vec![vec![c]]);
+
+        // the macro doesn't share the repeated `a * b`, so it needs one more multiplication than
+        // `MyCircuit` and no longer fits in 2^k rows
+        let circuit = MacroCircuit::new(a, b);
+        assert_accepts(k + 1, &circuit, vec![vec![c]]);
+
+        // negative case
+        assert_rejects(k + 1, &circuit, vec![vec![c + Fp::one()]]);
+
+        Ok(())
+    }
+}
@@ -1,24 +1,73 @@
 use halo2_proofs::{circuit::Value, dev::MockProver, pasta::Fp};
 use simple_example::MyCircuit;
 
+const USAGE: &str = "usage: simple-example [--constant <n>] [--a <n>] [--b <n>] [--k <n>]";
+
+// the command-line arguments, defaulting to `constant = 7, a = 2, b = 3, k = 4`
+struct Args {
+    constant: u64,
+    a: u64,
+    b: u64,
+    k: u32,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Args {
+            constant: 7,
+            a: 2,
+            b: 3,
+            k: 4,
+        };
+
+        while let Some(flag) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value for `{}`", flag))?;
+            let invalid = |_| format!("invalid value for `{}`: `{}`", flag, value);
+
+            match flag.as_str() {
+                "--constant" => parsed.constant = value.parse().map_err(invalid)?,
+                "--a" => parsed.a = value.parse().map_err(invalid)?,
+                "--b" => parsed.b = value.parse().map_err(invalid)?,
+                "--k" => parsed.k = value.parse().map_err(invalid)?,
+                _ => return Err(format!("unknown argument `{}`", flag)),
+            }
+        }
+
+        Ok(parsed)
+    }
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let k = 4;
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("error: {}\n{}", err, USAGE);
+            std::process::exit(2);
+        }
+    };
 
-    let constant = Fp::from(7);
-    let a = Fp::from(2);
-    let b = Fp::from(3);
-    let c = constant * a.square() * b.square();
+    let constant = Fp::from(args.constant);
+    let a = Fp::from(args.a);
+    let b = Fp::from(args.b);
 
     let circuit = MyCircuit::new(constant, Value::known(a), Value::known(b));
+    let public_inputs = circuit.expected_public();
 
-    let mut public_inputs = vec![c];
-    let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()])?;
-    assert_eq!(prover.verify(), Ok(()));
-
-    // negative case
-    public_inputs[0] += Fp::one();
-    let prover = MockProver::run(k, &circuit, vec![public_inputs])?;
-    assert!(prover.verify().is_err());
+    match MockProver::run(args.k, &circuit, vec![public_inputs.clone()])
+        .map(|prover| prover.verify())
+    {
+        Ok(Ok(())) => println!("verification passed: c = {:?}", public_inputs[0]),
+        Ok(Err(failures)) => {
+            eprintln!("verification failed: {:?}", failures);
+            std::process::exit(1);
+        }
+        Err(err) => {
+            eprintln!("verification failed: {}", err);
+            std::process::exit(1);
+        }
+    }
 
     Ok(())
 }
@@ -0,0 +1,658 @@
+//! End-to-end proving of `MyCircuit` with the IPA commitment scheme over the Pasta curves.
+
+use std::{
+    io::{self, Read, Write},
+    time::{Duration, Instant},
+};
+
+use blake2b_simd::Params as Blake2bParams;
+use halo2_proofs::{
+    circuit::Value,
+    pasta::{group::ff::PrimeField, EqAffine, Fp},
+    plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, Error, FloorPlanner, ProvingKey,
+        SingleVerifier, VerifyingKey,
+    },
+    poly::commitment::Params,
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+use rand_core::{OsRng, RngCore};
+
+use crate::{CircuitError, MyCircuit};
+
+// the circuit fits in 2^K rows
+pub const K: u32 = 4;
+
+// generate the public parameters. These are deterministic for a given `K`, so the prover and
+// verifier can derive them independently.
+pub fn params() -> Params<EqAffine> {
+    Params::new(K)
+}
+
+// generate the proving key for the shape of `circuit`. Only the constant is kept, the witnesses
+// are dropped.
+pub fn keygen<P: FloorPlanner>(
+    params: &Params<EqAffine>,
+    circuit: &MyCircuit<Fp, P>,
+) -> Result<ProvingKey<EqAffine>, Error> {
+    let empty_circuit = circuit.without_witnesses();
+    let vk = keygen_vk(params, &empty_circuit)?;
+    keygen_pk(params, vk, &empty_circuit)
+}
+
+// create a proof that `circuit` produces `public_inputs`
+pub fn prove<P: FloorPlanner>(
+    circuit: &MyCircuit<Fp, P>,
+    public_inputs: &[Fp],
+) -> Result<Vec<u8>, Error> {
+    prove_with_rng(circuit, public_inputs, OsRng)
+}
+
+// like `prove`, drawing the blinding factors from `rng`. With a seeded RNG the proof is
+// deterministic, which is only useful for tests: a proof must never reuse blinding factors.
+pub fn prove_with_rng<P: FloorPlanner>(
+    circuit: &MyCircuit<Fp, P>,
+    public_inputs: &[Fp],
+    rng: impl RngCore,
+) -> Result<Vec<u8>, Error> {
+    let params = params();
+    let pk = keygen(&params, circuit)?;
+
+    let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]);
+    create_proof(
+        &params,
+        &pk,
+        std::slice::from_ref(circuit),
+        &[&[public_inputs]],
+        rng,
+        &mut transcript,
+    )?;
+
+    Ok(transcript.finalize())
+}
+
+// how long each stage of `prove_timed` took
+#[derive(Debug, Clone, Copy)]
+pub struct ProveTimings {
+    pub params: Duration,
+    pub keygen: Duration,
+    pub prove: Duration,
+    pub verify: Duration,
+}
+
+// like `prove`, but also verify the proof and measure how long each stage takes. The timings are
+// logged when the `tracing` feature is enabled.
+pub fn prove_timed<P: FloorPlanner>(
+    circuit: &MyCircuit<Fp, P>,
+    public_inputs: &[Fp],
+) -> Result<(Vec<u8>, ProveTimings), Error> {
+    let start = Instant::now();
+    let params = params();
+    let params_time = start.elapsed();
+
+    let start = Instant::now();
+    let pk = keygen(&params, circuit)?;
+    let keygen_time = start.elapsed();
+
+    let start = Instant::now();
+    let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]);
+    create_proof(
+        &params,
+        &pk,
+        std::slice::from_ref(circuit),
+        &[&[public_inputs]],
+        OsRng,
+        &mut transcript,
+    )?;
+    let proof = transcript.finalize();
+    let prove_time = start.elapsed();
+
+    let start = Instant::now();
+    let strategy = SingleVerifier::new(&params);
+    let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(&proof[..]);
+    verify_proof(
+        &params,
+        pk.get_vk(),
+        strategy,
+        &[&[public_inputs]],
+        &mut transcript,
+    )?;
+    let verify_time = start.elapsed();
+
+    let timings = ProveTimings {
+        params: params_time,
+        keygen: keygen_time,
+        prove: prove_time,
+        verify: verify_time,
+    };
+    #[cfg(feature = "tracing")]
+    tracing::info!(?timings, "proved");
+
+    Ok((proof, timings))
+}
+
+// create a single proof that each of `circuits` produces the matching public inputs. The proving
+// key is generated from the first circuit, so all of them must share its constant.
+pub fn prove_batch<P: FloorPlanner>(
+    circuits: &[MyCircuit<Fp, P>],
+    instances: &[Vec<Fp>],
+) -> Result<Vec<u8>, CircuitError> {
+    if circuits.len() != instances.len() {
+        return Err(CircuitError::LengthMismatch {
+            expected: circuits.len(),
+            got: instances.len(),
+        });
+    }
+    let circuit = circuits.first().ok_or(CircuitError::Empty)?;
+    if let Some(index) = circuits
+        .iter()
+        .position(|other| other.constant != circuit.constant)
+    {
+        return Err(CircuitError::ConstantMismatch { index });
+    }
+
+    let params = params();
+    let pk = keygen(&params, circuit)?;
+
+    let instances = instances
+        .iter()
+        .map(|instance| [instance.as_slice()])
+        .collect::<Vec<_>>();
+    let instances = instances.iter().map(|c| &c[..]).collect::<Vec<_>>();
+
+    let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]);
+    create_proof(&params, &pk, circuits, &instances, OsRng, &mut transcript)?;
+
+    Ok(transcript.finalize())
+}
+
+// check a proof created by `prove_batch` against the verifying key and the public inputs of
+// every circuit
+pub fn verify_batch(vk: &VerifyingKey<EqAffine>, instances: &[Vec<Fp>], proof: &[u8]) -> bool {
+    let params = params();
+    let strategy = SingleVerifier::new(&params);
+    let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(proof);
+
+    let instances = instances
+        .iter()
+        .map(|instance| [instance.as_slice()])
+        .collect::<Vec<_>>();
+    let instances = instances.iter().map(|c| &c[..]).collect::<Vec<_>>();
+
+    verify_proof(&params, vk, strategy, &instances, &mut transcript).is_ok()
+}
+
+// the size in bytes of a proof that `circuit` produces `public_inputs`, laid out in 2^k rows.
+// The proof is generated and discarded.
+pub fn proof_size<P: FloorPlanner>(
+    k: u32,
+    circuit: &MyCircuit<Fp, P>,
+    public_inputs: &[Fp],
+) -> Result<usize, Error> {
+    let params = Params::<EqAffine>::new(k);
+    let pk = keygen(&params, circuit)?;
+
+    let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]);
+    create_proof(
+        &params,
+        &pk,
+        std::slice::from_ref(circuit),
+        &[&[public_inputs]],
+        OsRng,
+        &mut transcript,
+    )?;
+
+    Ok(transcript.finalize().len())
+}
+
+// check `proof` against the verifying key and the public inputs
+pub fn verify(vk: &VerifyingKey<EqAffine>, public_inputs: &[Fp], proof: &[u8]) -> bool {
+    let params = params();
+    let strategy = SingleVerifier::new(&params);
+    let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(proof);
+
+    verify_proof(&params, vk, strategy, &[&[public_inputs]], &mut transcript).is_ok()
+}
+
+// the index of the first of `candidates` that `proof` verifies against, if any
+pub fn verify_any(
+    vk: &VerifyingKey<EqAffine>,
+    proof: &[u8],
+    candidates: &[Vec<Fp>],
+) -> Option<usize> {
+    candidates
+        .iter()
+        .position(|public_inputs| verify(vk, public_inputs, proof))
+}
+
+// check that both proofs verify against the same verifying key and public inputs. Both are
+// always verified, so the time taken doesn't depend on which one fails.
+pub fn proofs_equivalent(
+    proof_a: &[u8],
+    proof_b: &[u8],
+    vk: &VerifyingKey<EqAffine>,
+    public_inputs: &[Fp],
+) -> bool {
+    let a = verify(vk, public_inputs, proof_a);
+    let b = verify(vk, public_inputs, proof_b);
+    a & b
+}
+
+// encode a public output as the canonical little-endian bytes of the field element
+pub fn serialize_public(output: Fp) -> Vec<u8> {
+    output.to_repr().as_ref().to_vec()
+}
+
+// decode a public output written by `serialize_public`. Returns `None` if `bytes` has the wrong
+// length or isn't a canonical encoding.
+pub fn deserialize_public(bytes: &[u8]) -> Option<Fp> {
+    let mut repr = <Fp as PrimeField>::Repr::default();
+    if bytes.len() != repr.as_ref().len() {
+        return None;
+    }
+    repr.as_mut().copy_from_slice(bytes);
+
+    Option::from(Fp::from_repr(repr))
+}
+
+// the length of a verifying key fingerprint, in bytes
+const FINGERPRINT_LEN: usize = 64;
+
+// hash the pinned representation of `vk`, which covers everything the key is derived from
+fn fingerprint(vk: &VerifyingKey<EqAffine>) -> [u8; FINGERPRINT_LEN] {
+    let pinned = format!("{:?}", vk.pinned());
+
+    let hash = Blake2bParams::new()
+        .hash_length(FINGERPRINT_LEN)
+        .personal(b"MyCircuit-Vk-Fp")
+        .hash(pinned.as_bytes());
+
+    let mut fingerprint = [0u8; FINGERPRINT_LEN];
+    fingerprint.copy_from_slice(hash.as_bytes());
+    fingerprint
+}
+
+// write the verifying key for a `MyCircuit` with the given constant.
+//
+// halo2_proofs 0.3 can't serialize a `VerifyingKey` directly, but the key is fully determined
+// by the parameters and the circuit shape. We store the circuit constant along with a
+// fingerprint of the key, so that `read_vk` can regenerate the key and check that it matches
+// what was written.
+pub fn write_vk<W: Write>(
+    vk: &VerifyingKey<EqAffine>,
+    constant: Fp,
+    writer: &mut W,
+) -> io::Result<()> {
+    writer.write_all(constant.to_repr().as_ref())?;
+    writer.write_all(&fingerprint(vk))
+}
+
+// read a verifying key written by `write_vk`
+pub fn read_vk<R: Read>(
+    reader: &mut R,
+    params: &Params<EqAffine>,
+) -> io::Result<VerifyingKey<EqAffine>> {
+    let mut repr = <Fp as PrimeField>::Repr::default();
+    reader.read_exact(repr.as_mut())?;
+    let constant = Option::from(Fp::from_repr(repr))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid constant"))?;
+
+    let mut expected = [0u8; FINGERPRINT_LEN];
+    reader.read_exact(&mut expected)?;
+
+    let circuit = MyCircuit::new(constant, Value::unknown(), Value::unknown());
+    let vk = keygen_vk(params, &circuit).map_err(|e| io::Error::other(format!("{:?}", e)))?;
+
+    if fingerprint(&vk) != expected {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "verifying key does not match the circuit",
+        ));
+    }
+
+    Ok(vk)
+}
+
+// verifies proofs of `MyCircuit` holding only the verifying key and the public parameters, with
+// no proving key
+pub struct Verifier {
+    params: Params<EqAffine>,
+    vk: VerifyingKey<EqAffine>,
+}
+
+impl Verifier {
+    pub fn new(params: Params<EqAffine>, vk: VerifyingKey<EqAffine>) -> Self {
+        Self { params, vk }
+    }
+
+    // read a verifying key written by `write_vk`, deriving the public parameters
+    pub fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let params = params();
+        let vk = read_vk(reader, &params)?;
+
+        Ok(Self::new(params, vk))
+    }
+
+    pub fn vk(&self) -> &VerifyingKey<EqAffine> {
+        &self.vk
+    }
+
+    // check `proof` against the public inputs
+    pub fn verify(&self, public_inputs: &[Fp], proof: &[u8]) -> bool {
+        let strategy = SingleVerifier::new(&self.params);
+        let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(proof);
+
+        verify_proof(
+            &self.params,
+            &self.vk,
+            strategy,
+            &[&[public_inputs]],
+            &mut transcript,
+        )
+        .is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use halo2_proofs::circuit::SimpleFloorPlanner;
+
+    use crate::{field_ext::field, test_util::assert_accepts};
+
+    #[test]
+    fn prove_and_verify() -> Result<(), Box<dyn std::error::Error>> {
+        let constant = Fp::from(7);
+        let a = Fp::from(2);
+        let b = Fp::from(3);
+        let c = constant * a.square() * b.square();
+
+        let circuit = MyCircuit::new(constant, Value::known(a), Value::known(b));
+
+        let params = params();
+        let pk = keygen(&params, &circuit)?;
+
+        let mut proof = prove(&circuit, &[c])?;
+        assert!(verify(pk.get_vk(), &[c], &proof));
+
+        // negative case: the wrong public input
+        assert!(!verify(pk.get_vk(), &[c + Fp::one()], &proof));
+
+        // negative case: a tampered proof
+        proof[0] ^= 1;
+        assert!(!verify(pk.get_vk(), &[c], &proof));
+
+        Ok(())
+    }
+
+    #[test]
+    fn vk_serialization() -> Result<(), Box<dyn std::error::Error>> {
+        let constant = Fp::from(7);
+        let a = Fp::from(2);
+        let b = Fp::from(3);
+        let c = constant * a.square() * b.square();
+
+        let circuit = MyCircuit::new(constant, Value::known(a), Value::known(b));
+
+        let params = params();
+        let pk = keygen(&params, &circuit)?;
+
+        let mut bytes = vec![];
+        write_vk(pk.get_vk(), constant, &mut bytes)?;
+        let vk = read_vk(&mut &bytes[..], &params)?;
+
+        // both keys verify the same proof
+        let proof = prove(&circuit, &[c])?;
+        assert!(verify(pk.get_vk(), &[c], &proof));
+        assert!(verify(&vk, &[c], &proof));
+
+        // negative case: a key written for a different constant is rejected
+        let mut bytes = vec![];
+        write_vk(pk.get_vk(), constant + Fp::one(), &mut bytes)?;
+        assert!(read_vk(&mut &bytes[..], &params).is_err());
+
+        // negative case: a corrupted fingerprint is rejected
+        let mut bytes = vec![];
+        write_vk(pk.get_vk(), constant, &mut bytes)?;
+        *bytes.last_mut().ok_or("the key was written")? ^= 1;
+        assert!(read_vk(&mut &bytes[..], &params).is_err());
+
+        // negative case: a truncated key is rejected
+        bytes.pop();
+        assert!(read_vk(&mut &bytes[..], &params).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn batch_proof() -> Result<(), Box<dyn std::error::Error>> {
+        let constant = Fp::from(7);
+        let circuits = [(2, 3), (4, 5)]
+            .map(|(a, b)| MyCircuit::new(constant, Value::known(field(a)), Value::known(field(b))));
+        let instances = vec![
+            vec![constant * Fp::from(2 * 2 * 3 * 3)],
+            vec![constant * Fp::from(4 * 4 * 5 * 5)],
+        ];
+
+        let params = params();
+        let pk = keygen(&params, &circuits[0])?;
+        let proof = prove_batch(&circuits, &instances)?;
+        assert!(verify_batch(pk.get_vk(), &instances, &proof));
+
+        // negative cases: swapped public inputs, and a missing instance
+        let swapped = vec![instances[1].clone(), instances[0].clone()];
+        assert!(!verify_batch(pk.get_vk(), &swapped, &proof));
+        assert!(matches!(
+            prove_batch(&circuits, &instances[..1]),
+            Err(CircuitError::LengthMismatch {
+                expected: 2,
+                got: 1
+            })
+        ));
+
+        // negative cases: no circuits, and a circuit with a different constant
+        assert!(matches!(
+            prove_batch::<SimpleFloorPlanner>(&[], &[]),
+            Err(CircuitError::Empty)
+        ));
+        let mismatched = [(2, 3), (4, 5)]
+            .map(|(a, b)| MyCircuit::new(field(a), Value::known(field(a)), Value::known(field(b))));
+        assert!(matches!(
+            prove_batch(&mismatched, &instances),
+            Err(CircuitError::ConstantMismatch { index: 1 })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn equivalent_proofs() -> Result<(), Box<dyn std::error::Error>> {
+        let constant = Fp::from(7);
+        let circuit = MyCircuit::new(
+            constant,
+            Value::known(Fp::from(2)),
+            Value::known(Fp::from(3)),
+        );
+        let public_inputs = [Fp::from(252)];
+
+        let params = params();
+        let pk = keygen(&params, &circuit)?;
+
+        // the proofs are randomized, so they differ byte-wise but both verify
+        let proof_a = prove(&circuit, &public_inputs)?;
+        let proof_b = prove(&circuit, &public_inputs)?;
+        assert_ne!(proof_a, proof_b);
+        assert!(proofs_equivalent(
+            &proof_a,
+            &proof_b,
+            pk.get_vk(),
+            &public_inputs
+        ));
+
+        // negative case: a tampered proof
+        let mut tampered = proof_b.clone();
+        tampered[0] ^= 1;
+        assert!(!proofs_equivalent(
+            &proof_a,
+            &tampered,
+            pk.get_vk(),
+            &public_inputs
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn proof_size_is_stable() -> Result<(), Box<dyn std::error::Error>> {
+        let k = 4;
+
+        let circuit = MyCircuit::new(
+            Fp::from(7),
+            Value::known(Fp::from(2)),
+            Value::known(Fp::from(3)),
+        );
+        let public_inputs = [Fp::from(252)];
+
+        // the proofs are randomized, but their size only depends on the circuit and `k`
+        let size = proof_size(k, &circuit, &public_inputs)?;
+        assert_eq!(size, proof_size(k, &circuit, &public_inputs)?);
+        assert_eq!(size, prove(&circuit, &public_inputs)?.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn verifier() -> Result<(), Box<dyn std::error::Error>> {
+        let constant = Fp::from(7);
+        let circuit = MyCircuit::new(
+            constant,
+            Value::known(Fp::from(2)),
+            Value::known(Fp::from(3)),
+        );
+        let public_inputs = [Fp::from(252)];
+
+        // the prover's side: write the verifying key and create a proof
+        let mut bytes = vec![];
+        {
+            let params = params();
+            let pk = keygen(&params, &circuit)?;
+            write_vk(pk.get_vk(), constant, &mut bytes)?;
+        }
+        let proof = prove(&circuit, &public_inputs)?;
+
+        // the verifier's side only reads the key
+        let verifier = Verifier::read(&mut &bytes[..])?;
+        assert!(verifier.verify(&public_inputs, &proof));
+
+        // negative case
+        assert!(!verifier.verify(&[Fp::from(253)], &proof));
+
+        Ok(())
+    }
+
+    #[test]
+    fn timed_proof() -> Result<(), Box<dyn std::error::Error>> {
+        let circuit = MyCircuit::new(
+            Fp::from(7),
+            Value::known(Fp::from(2)),
+            Value::known(Fp::from(3)),
+        );
+
+        let (proof, timings) = prove_timed(&circuit, &[Fp::from(252)])?;
+        assert!(!proof.is_empty());
+        for duration in [
+            timings.params,
+            timings.keygen,
+            timings.prove,
+            timings.verify,
+        ] {
+            assert!(!duration.is_zero());
+        }
+
+        // negative case: the proof doesn't verify against the wrong public input
+        assert!(prove_timed(&circuit, &[Fp::from(253)]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn public_input_serialization() -> Result<(), Box<dyn std::error::Error>> {
+        let k = 4;
+
+        let circuit = MyCircuit::new(
+            Fp::from(7),
+            Value::known(Fp::from(2)),
+            Value::known(Fp::from(3)),
+        );
+        let output = circuit.public_output().ok_or("the witnesses are known")?;
+
+        let bytes = serialize_public(output);
+        assert_eq!(bytes.len(), 32);
+        let decoded = deserialize_public(&bytes).ok_or("a canonical encoding")?;
+        assert_eq!(decoded, Fp::from(252));
+        assert_accepts(k, &circuit, vec![vec![decoded]]);
+
+        // negative cases: the wrong length, and a non-canonical encoding
+        assert!(deserialize_public(&bytes[1..]).is_none());
+        assert!(deserialize_public(&[0xff; 32]).is_none());
+
+        // there's no output without the witnesses
+        assert!(MyCircuit::<Fp>::default().public_output().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn seeded_proof() -> Result<(), Box<dyn std::error::Error>> {
+        use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+
+        let circuit = MyCircuit::new(
+            Fp::from(7),
+            Value::known(Fp::from(2)),
+            Value::known(Fp::from(3)),
+        );
+        let public_inputs = [Fp::from(252)];
+
+        // the same seed gives the same proof
+        let proof_a = prove_with_rng(&circuit, &public_inputs, ChaCha20Rng::seed_from_u64(42))?;
+        let proof_b = prove_with_rng(&circuit, &public_inputs, ChaCha20Rng::seed_from_u64(42))?;
+        assert_eq!(proof_a, proof_b);
+
+        let pk = keygen(&params(), &circuit)?;
+        assert!(verify(pk.get_vk(), &public_inputs, &proof_a));
+        assert!(verify(pk.get_vk(), &public_inputs, &proof_b));
+
+        // a different seed gives a different, but equally valid, proof
+        let proof_c = prove_with_rng(&circuit, &public_inputs, ChaCha20Rng::seed_from_u64(43))?;
+        assert_ne!(proof_a, proof_c);
+        assert!(verify(pk.get_vk(), &public_inputs, &proof_c));
+
+        Ok(())
+    }
+
+    #[test]
+    fn verify_any_public_input() -> Result<(), Box<dyn std::error::Error>> {
+        let circuit = MyCircuit::new(
+            Fp::from(7),
+            Value::known(Fp::from(2)),
+            Value::known(Fp::from(3)),
+        );
+
+        let pk = keygen(&params(), &circuit)?;
+        let proof = prove(&circuit, &circuit.expected_public())?;
+
+        let candidates = [
+            vec![Fp::from(251)],
+            vec![Fp::from(252)],
+            vec![Fp::from(253)],
+        ];
+        assert_eq!(verify_any(pk.get_vk(), &proof, &candidates), Some(1));
+
+        // negative case: no candidate matches
+        assert_eq!(verify_any(pk.get_vk(), &proof, &candidates[2..]), None);
+        assert_eq!(verify_any(pk.get_vk(), &proof, &[]), None);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,232 @@
+//! A chip constraining numbers to small ranges with a lookup table.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use crate::Number;
+
+// the largest supported range is [0, 2^MAX_BITS)
+pub const MAX_BITS: usize = 8;
+
+#[derive(Debug, Clone)]
+pub struct RangeConfig {
+    value: Column<Advice>,
+    shift: Column<Fixed>,
+    table: TableColumn,
+    s_lookup: Selector,
+    s_shift: Selector,
+}
+
+pub struct RangeChip<F: Field> {
+    config: RangeConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> Chip<F> for RangeChip<F> {
+    type Config = RangeConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field> RangeChip<F> {
+    pub fn construct(config: RangeConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        value: Column<Advice>,
+        shift: Column<Fixed>,
+    ) -> RangeConfig {
+        meta.enable_equality(value);
+
+        let table = meta.lookup_table_column();
+        let s_lookup = meta.complex_selector();
+        let s_shift = meta.selector();
+
+        // look up every enabled value in the table [0, 2^MAX_BITS). Disabled rows look up 0,
+        // which is always in the table.
+        meta.lookup(|meta| {
+            let value = meta.query_advice(value, Rotation::cur());
+            let s_lookup = meta.query_selector(s_lookup);
+
+            vec![(s_lookup * value, table)]
+        });
+
+        // create the shifting gate
+        meta.create_gate("shift", |meta| {
+            // v | f | s_shift
+            //----------------
+            // x | 2^(MAX_BITS - n) | s_shift
+            // shifted
+            let x = meta.query_advice(value, Rotation::cur());
+            let shifted = meta.query_advice(value, Rotation::next());
+            let shift = meta.query_fixed(shift);
+            let s_shift = meta.query_selector(s_shift);
+
+            // the polynomial is: s_shift * (x * shift - shifted) == 0
+            vec![s_shift * (x * shift - shifted)]
+        });
+
+        RangeConfig {
+            value,
+            shift,
+            table,
+            s_lookup,
+            s_shift,
+        }
+    }
+
+    // load the table of all values in [0, 2^MAX_BITS). This must be called once per circuit.
+    pub fn load_table(&self, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let config = self.config();
+
+        layouter.assign_table(
+            || "range table",
+            |mut table| {
+                let mut value = F::ZERO;
+                for offset in 0..1 << MAX_BITS {
+                    table.assign_cell(|| "value", config.table, offset, || Value::known(value))?;
+                    value += F::ONE;
+                }
+
+                Ok(())
+            },
+        )
+    }
+
+    // constrain `x` to [0, 2^n). Both `x` and `x * 2^(MAX_BITS - n)` are looked up in the
+    // table: the first lookup bounds `x` so that the shift cannot wrap around the modulus, and
+    // the second then fails for any `x` with bits at or above `n`.
+    pub fn range_check(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: &Number<F>,
+        n: usize,
+    ) -> Result<(), Error> {
+        if n > MAX_BITS {
+            return Err(Error::Synthesis);
+        }
+
+        let config = self.config();
+        let shift = (n..MAX_BITS).fold(F::ONE, |shift, _| shift.double());
+
+        layouter.assign_region(
+            || "range check",
+            |mut region| {
+                config.s_lookup.enable(&mut region, 0)?;
+                config.s_lookup.enable(&mut region, 1)?;
+                config.s_shift.enable(&mut region, 0)?;
+
+                x.0.copy_advice(|| "x", &mut region, config.value, 0)?;
+                region.assign_fixed(|| "shift", config.shift, 0, || Value::known(shift))?;
+
+                let shifted = x.value().map(|x| *x * shift);
+                region.assign_advice(|| "shifted", config.value, 1, || shifted)?;
+
+                Ok(())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use halo2_proofs::{circuit::SimpleFloorPlanner, pasta::Fp, plonk::Circuit};
+
+    use crate::{
+        test_util::{assert_accepts, assert_rejects, configure},
+        FieldChip, FieldConfig, Instructions,
+    };
+
+    // Constrains the private input to [0, 2^bits) with the `RangeChip`
+    #[derive(Default)]
+    pub struct RangeCheckCircuit<F: Field> {
+        x: Value<F>,
+        bits: usize,
+    }
+
+    impl<F: Field> RangeCheckCircuit<F> {
+        pub fn new(x: Value<F>, bits: usize) -> Self {
+            Self { x, bits }
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct RangeCheckConfig {
+        field: FieldConfig,
+        range: RangeConfig,
+    }
+
+    impl<F: Field> Circuit<F> for RangeCheckCircuit<F> {
+        type Config = RangeCheckConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                x: Value::unknown(),
+                bits: self.bits,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let field = configure(meta);
+            let value = meta.advice_column();
+            let shift = meta.fixed_column();
+            let range = RangeChip::configure(meta, value, shift);
+
+            RangeCheckConfig { field, range }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let field_chip = FieldChip::<F>::construct(config.field);
+            let range_chip = RangeChip::<F>::construct(config.range);
+
+            range_chip.load_table(layouter.namespace(|| "load table"))?;
+
+            let x = field_chip.load_private(layouter.namespace(|| "load x"), self.x)?;
+            range_chip.range_check(layouter.namespace(|| "range check x"), &x, self.bits)
+        }
+    }
+
+    #[test]
+    fn range_check() {
+        // the lookup table takes 2^8 rows
+        let k = 9;
+
+        for (x, bits) in [(0, 0), (0, 4), (15, 4), (255, 8), (1, 1)] {
+            let circuit = RangeCheckCircuit::new(Value::known(Fp::from(x)), bits);
+            assert_accepts(k, &circuit, vec![vec![]]);
+        }
+
+        // negative cases
+        for (x, bits) in [(1, 0), (16, 4), (256, 8), (2, 1)] {
+            let circuit = RangeCheckCircuit::new(Value::known(Fp::from(x)), bits);
+            assert_rejects(k, &circuit, vec![vec![]]);
+        }
+        let circuit = RangeCheckCircuit::new(Value::known(-Fp::one()), 8);
+        assert_rejects(k, &circuit, vec![vec![]]);
+    }
+}
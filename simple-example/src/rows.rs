@@ -0,0 +1,126 @@
+//! Counting the rows a circuit's regions consume, without proving or checking anything.
+
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::Value,
+    plonk::{Advice, Any, Assigned, Assignment, Column, Error, Fixed, Instance, Selector},
+};
+
+// an `Assignment` that only records the highest row touched by the floor planner, and the number
+// of copy constraints
+#[derive(Default)]
+pub(crate) struct RowCounter {
+    rows: usize,
+    #[cfg(test)]
+    copies: usize,
+}
+
+impl RowCounter {
+    // the number of rows used, i.e. one past the highest row touched
+    pub(crate) fn rows(&self) -> usize {
+        self.rows
+    }
+
+    // the number of copy constraints, including those to instance cells
+    #[cfg(test)]
+    pub(crate) fn copies(&self) -> usize {
+        self.copies
+    }
+
+    fn touch(&mut self, row: usize) {
+        self.rows = self.rows.max(row + 1);
+    }
+}
+
+impl<F: Field> Assignment<F> for RowCounter {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.touch(row);
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Advice>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.touch(row);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Fixed>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.touch(row);
+        Ok(())
+    }
+
+    fn copy(
+        &mut self,
+        _: Column<Any>,
+        left_row: usize,
+        _: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        // this also covers the rows of the instance column used by `constrain_instance`
+        self.touch(left_row);
+        self.touch(right_row);
+        #[cfg(test)]
+        {
+            self.copies += 1;
+        }
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        _: usize,
+        _: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
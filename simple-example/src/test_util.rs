@@ -0,0 +1,425 @@
+//! Helpers for checking circuits with the `MockProver`.
+
+use std::{
+    fs,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{Cell, Layouter, Region, SimpleFloorPlanner, Table, Value},
+    dev::MockProver,
+    pasta::{EqAffine, Fp},
+    plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, Column, ConstraintSystem, Error,
+        Fixed, FloorPlanner, Instance, SingleVerifier,
+    },
+    poly::commitment::Params,
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+use rand_core::OsRng;
+
+use crate::{rows::RowCounter, FieldChip, FieldConfig, MyCircuit};
+
+// assert that `circuit` is satisfied by the `instance` columns
+pub fn assert_accepts<F, C>(k: u32, circuit: &C, instance: Vec<Vec<F>>)
+where
+    F: Field + Ord,
+    C: Circuit<F>,
+{
+    debug_check(k, circuit, instance);
+}
+
+// assert that `circuit` is satisfied by the `instance` columns. `MockProver::assert_satisfied`
+// prints each failing constraint, with the cells it queried, before panicking.
+pub fn debug_check<F, C>(k: u32, circuit: &C, instance: Vec<Vec<F>>)
+where
+    F: Field + Ord,
+    C: Circuit<F>,
+{
+    let prover = MockProver::run(k, circuit, instance).expect("the circuit synthesizes");
+    prover.assert_satisfied();
+}
+
+// assert that `circuit` is not satisfied by the `instance` columns
+pub fn assert_rejects<F, C>(k: u32, circuit: &C, instance: Vec<Vec<F>>)
+where
+    F: Field + Ord,
+    C: Circuit<F>,
+{
+    let prover = MockProver::run(k, circuit, instance).expect("the circuit synthesizes");
+    assert!(prover.verify().is_err());
+}
+
+// assert that the `MockProver` accepts `circuit` with the `instance` columns if and only if a real
+// IPA proof for them verifies. Both are always run. This catches circuits the `MockProver` is
+// more lenient with, e.g. a constant loaded into a column not enabled with `enable_constant`.
+pub fn cross_check<C: Circuit<Fp>>(k: u32, circuit: &C, instance: Vec<Vec<Fp>>) {
+    let mock = MockProver::run(k, circuit, instance.clone()).map(|prover| prover.verify());
+    let mock = matches!(mock, Ok(Ok(())));
+
+    let real = || -> Result<(), halo2_proofs::plonk::Error> {
+        let params = Params::<EqAffine>::new(k);
+        let vk = keygen_vk(&params, &circuit.without_witnesses())?;
+        let pk = keygen_pk(&params, vk, &circuit.without_witnesses())?;
+
+        let instance = instance.iter().map(|c| c.as_slice()).collect::<Vec<_>>();
+        let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]);
+        create_proof(
+            &params,
+            &pk,
+            std::slice::from_ref(circuit),
+            &[&instance],
+            OsRng,
+            &mut transcript,
+        )?;
+        let proof = transcript.finalize();
+
+        let strategy = SingleVerifier::new(&params);
+        let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(&proof[..]);
+        verify_proof(
+            &params,
+            pk.get_vk(),
+            strategy,
+            &[&instance],
+            &mut transcript,
+        )
+    };
+    let real = real().is_ok();
+
+    assert_eq!(mock, real, "the MockProver and the real prover disagree");
+}
+
+// create the columns shared by the test circuits
+pub fn configure<F: Field>(meta: &mut ConstraintSystem<F>) -> FieldConfig {
+    let advice = [meta.advice_column(), meta.advice_column()];
+    let instance = meta.instance_column();
+    let constant = meta.fixed_column();
+
+    FieldChip::configure(meta, advice, &[instance], constant)
+}
+
+// find the smallest `k` at which the circuit fits and verifies
+pub fn smallest_k<C: Circuit<Fp>>(circuit: &C, instance: Vec<Vec<Fp>>) -> u32 {
+    (1..=16)
+        .find(|&k| {
+            MockProver::run(k, circuit, instance.clone())
+                .map(|prover| prover.verify().is_ok())
+                .unwrap_or(false)
+        })
+        .expect("circuit does not fit in 2^16 rows")
+}
+
+// lay out `circuit` without checking anything, counting the rows and copy constraints it uses.
+// `constants` picks the fixed columns enabled for constants out of the circuit's config.
+pub fn layout<C, G>(circuit: &C, constants: G) -> RowCounter
+where
+    C: Circuit<Fp>,
+    G: FnOnce(&C::Config) -> Vec<Column<Fixed>>,
+{
+    let mut meta = ConstraintSystem::default();
+    let config = C::configure(&mut meta);
+
+    let mut counter = RowCounter::default();
+    let constants = constants(&config);
+    C::FloorPlanner::synthesize(&mut counter, circuit, config, constants)
+        .expect("laying out the circuit can't fail");
+
+    counter
+}
+
+// A path in the temporary directory that no other test uses, e.g. one running concurrently in the
+// same process or in another process. The file is removed when the path is dropped.
+pub struct TempPath(PathBuf);
+
+impl TempPath {
+    pub fn new(name: &str) -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+        let index = NEXT.fetch_add(1, Ordering::Relaxed);
+        let name = format!("simple-example-{}-{}-{}", process::id(), index, name);
+        Self(std::env::temp_dir().join(name))
+    }
+}
+
+impl AsRef<Path> for TempPath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        // the file may never have been created
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+// A circuit laying out whatever `synthesize` does with a `FieldChip` of `W` advice columns and
+// `I` instance columns. The tests build one per case instead of declaring a circuit type for it.
+pub struct TestCircuit<F, S, const W: usize = 2, const I: usize = 1> {
+    synthesize: S,
+    _marker: PhantomData<F>,
+}
+
+impl<F, S> TestCircuit<F, S>
+where
+    F: Field,
+    S: Fn(FieldChip<F>, TestLayouter<'_, F>) -> Result<(), Error>,
+{
+    pub fn new(synthesize: S) -> Self {
+        Self::with_columns(synthesize)
+    }
+}
+
+impl<F, S, const W: usize, const I: usize> TestCircuit<F, S, W, I>
+where
+    F: Field,
+    S: Fn(FieldChip<F, W>, TestLayouter<'_, F>) -> Result<(), Error>,
+{
+    // like `new`, for a chip with other than two advice columns or one instance column
+    pub fn with_columns(synthesize: S) -> Self {
+        Self {
+            synthesize,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F, S, const W: usize, const I: usize> Circuit<F> for TestCircuit<F, S, W, I>
+where
+    F: Field,
+    S: Fn(FieldChip<F, W>, TestLayouter<'_, F>) -> Result<(), Error> + Clone,
+{
+    type Config = FieldConfig<W>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    // the witnesses are captured by `synthesize`, so they are kept
+    fn without_witnesses(&self) -> Self {
+        Self::with_columns(self.synthesize.clone())
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); W].map(|_| meta.advice_column());
+        let instance = [(); I].map(|_| meta.instance_column());
+        let constant = meta.fixed_column();
+
+        FieldChip::configure(meta, advice, &instance, constant)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        (self.synthesize)(FieldChip::construct(config), TestLayouter(&mut layouter))
+    }
+}
+
+// The layouter passed to the closure of a `TestCircuit`. Closures can't be generic, so this hides
+// the layouter of the floor planner behind `DynLayouter`.
+pub struct TestLayouter<'a, F: Field>(&'a mut dyn DynLayouter<F>);
+
+impl<F: Field> Layouter<F> for TestLayouter<'_, F> {
+    type Root = Self;
+
+    fn assign_region<A, AR, N, NR>(&mut self, name: N, mut assignment: A) -> Result<AR, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        // the floor planner may run `assignment` more than once, the last run is the real one
+        let mut result = None;
+        self.0.dyn_assign_region(name().into(), &mut |region| {
+            result = Some(assignment(region)?);
+            Ok(())
+        })?;
+        Ok(result.expect("the region was assigned"))
+    }
+
+    fn assign_table<A, N, NR>(&mut self, name: N, mut assignment: A) -> Result<(), Error>
+    where
+        A: FnMut(Table<'_, F>) -> Result<(), Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        self.0.dyn_assign_table(name().into(), &mut assignment)
+    }
+
+    fn constrain_instance(
+        &mut self,
+        cell: Cell,
+        column: Column<Instance>,
+        row: usize,
+    ) -> Result<(), Error> {
+        self.0.dyn_constrain_instance(cell, column, row)
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.0.dyn_push_namespace(name_fn().into())
+    }
+
+    fn pop_namespace(&mut self, gadget_name: Option<String>) {
+        self.0.dyn_pop_namespace(gadget_name)
+    }
+}
+
+// the object-safe part of `Layouter`
+pub trait DynLayouter<F: Field> {
+    fn dyn_assign_region(
+        &mut self,
+        name: String,
+        assignment: &mut dyn FnMut(Region<'_, F>) -> Result<(), Error>,
+    ) -> Result<(), Error>;
+
+    fn dyn_assign_table(
+        &mut self,
+        name: String,
+        assignment: &mut dyn FnMut(Table<'_, F>) -> Result<(), Error>,
+    ) -> Result<(), Error>;
+
+    fn dyn_constrain_instance(
+        &mut self,
+        cell: Cell,
+        column: Column<Instance>,
+        row: usize,
+    ) -> Result<(), Error>;
+
+    fn dyn_push_namespace(&mut self, name: String);
+
+    fn dyn_pop_namespace(&mut self, gadget_name: Option<String>);
+}
+
+impl<F: Field, L: Layouter<F>> DynLayouter<F> for L {
+    fn dyn_assign_region(
+        &mut self,
+        name: String,
+        assignment: &mut dyn FnMut(Region<'_, F>) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        self.assign_region(|| name.clone(), assignment)
+    }
+
+    fn dyn_assign_table(
+        &mut self,
+        name: String,
+        assignment: &mut dyn FnMut(Table<'_, F>) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        self.assign_table(|| name.clone(), assignment)
+    }
+
+    fn dyn_constrain_instance(
+        &mut self,
+        cell: Cell,
+        column: Column<Instance>,
+        row: usize,
+    ) -> Result<(), Error> {
+        self.constrain_instance(cell, column, row)
+    }
+
+    fn dyn_push_namespace(&mut self, name: String) {
+        self.get_root().push_namespace(|| name)
+    }
+
+    fn dyn_pop_namespace(&mut self, gadget_name: Option<String>) {
+        self.get_root().pop_namespace(gadget_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use halo2_proofs::circuit::Chip;
+
+    #[test]
+    fn debug_check_rejects_bad_witness() -> Result<(), Box<dyn std::error::Error>> {
+        let k = 4;
+
+        // a satisfied circuit passes
+        let circuit = MyCircuit::new(
+            Fp::from(7),
+            Value::known(Fp::from(2)),
+            Value::known(Fp::from(3)),
+        );
+        debug_check(k, &circuit, vec![vec![Fp::from(252)]]);
+
+        // lays out the `mul` region by hand with a product that is off by one
+        let broken_mul = TestCircuit::new(|field_chip, mut layouter| {
+            let config = field_chip.config();
+
+            layouter.assign_region(
+                || "broken mul",
+                |mut region| {
+                    config.s_op.enable(&mut region, 0)?;
+                    region.assign_fixed(|| "mode", config.mode, 0, || Value::known(Fp::one()))?;
+
+                    region.assign_advice(
+                        || "lhs",
+                        config.advice[0],
+                        0,
+                        || Value::known(Fp::from(2)),
+                    )?;
+                    region.assign_advice(
+                        || "rhs",
+                        config.advice[1],
+                        0,
+                        || Value::known(Fp::from(3)),
+                    )?;
+                    region.assign_advice(
+                        || "out",
+                        config.advice[0],
+                        1,
+                        || Value::known(Fp::from(7)),
+                    )?;
+                    Ok(())
+                },
+            )
+        });
+
+        // the failure is reported against the `op` gate
+        let prover = MockProver::run(k, &broken_mul, vec![vec![]])?;
+        let failures = prover.verify().err().ok_or("expected a failure")?;
+        assert!(
+            failures
+                .iter()
+                .any(|failure| failure.to_string().contains("('op')")),
+            "{:?}",
+            failures
+        );
+
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(|_| {}));
+        let result = std::panic::catch_unwind(|| debug_check(k, &broken_mul, vec![vec![]]));
+        std::panic::set_hook(hook);
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn cross_check_agrees() {
+        let k = 4;
+
+        let circuit = MyCircuit::new(
+            Fp::from(7),
+            Value::known(Fp::from(2)),
+            Value::known(Fp::from(3)),
+        );
+
+        // both provers accept the true output, and both reject any other
+        cross_check(k, &circuit, vec![vec![Fp::from(252)]]);
+        cross_check(k, &circuit, vec![vec![Fp::from(253)]]);
+    }
+}
@@ -0,0 +1,296 @@
+//! Tests of the instructions combining many values.
+
+use halo2_proofs::{
+    circuit::{Layouter, Value},
+    dev::MockProver,
+    pasta::Fp,
+};
+
+use crate::{
+    circuits::{EvalPolyCircuit, ProductCircuit, SumOfSquaresCircuit},
+    field_ext::{field, values},
+    test_util::{assert_accepts, assert_rejects, TestCircuit},
+    FoldOp, Instructions, MyCircuit, Op,
+};
+
+#[test]
+fn product() {
+    let k = 5;
+
+    for n in [1, 2, 5] {
+        let inputs = (1..=n).map(|i| Fp::from(i + 1)).collect::<Vec<_>>();
+        let product = inputs.iter().product::<Fp>();
+
+        let circuit = ProductCircuit::new(inputs.into_iter().map(Value::known).collect());
+
+        assert_accepts(k, &circuit, vec![vec![product]]);
+
+        // negative case
+        assert_rejects(k, &circuit, vec![vec![product + Fp::one()]]);
+    }
+}
+
+#[test]
+fn inner_product() {
+    let k = 5;
+
+    let circuit = |xs: Vec<Value<Fp>>, ys: Vec<Value<Fp>>| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let xs = field_chip.load_private_many(layouter.namespace(|| "load xs"), &xs)?;
+            let ys = field_chip.load_private_many(layouter.namespace(|| "load ys"), &ys)?;
+
+            let out = field_chip.inner_product(layouter.namespace(|| "xs . ys"), &xs, &ys)?;
+
+            field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0, 0)
+        })
+    };
+
+    let xs = values(&[1, 2, 3]);
+    let ys = values(&[4, 5, 6]);
+    let out = Fp::from(32);
+
+    let circuit_ok = circuit(xs.clone(), ys.clone());
+    assert_accepts(k, &circuit_ok, vec![vec![out]]);
+
+    // negative case
+    assert_rejects(k, &circuit_ok, vec![vec![out + Fp::one()]]);
+
+    // mismatched lengths are rejected at synthesis
+    let circuit = circuit(xs, ys[..2].to_vec());
+    assert!(MockProver::run(k, &circuit, vec![vec![out]]).is_err());
+}
+
+#[test]
+fn sum() {
+    let k = 5;
+
+    for n in [0, 1, 4] {
+        let inputs = (1..=n).map(Fp::from).collect::<Vec<_>>();
+        let sum = inputs.iter().sum::<Fp>();
+
+        let inputs = inputs.into_iter().map(Value::known).collect::<Vec<_>>();
+        let circuit = TestCircuit::new(move |field_chip, mut layouter| {
+            let inputs =
+                field_chip.load_private_many(layouter.namespace(|| "load inputs"), &inputs)?;
+
+            let sum = field_chip.sum(layouter.namespace(|| "sum"), &inputs)?;
+
+            field_chip.expose_public(layouter.namespace(|| "expose sum"), sum, 0, 0)
+        });
+        assert_accepts(k, &circuit, vec![vec![sum]]);
+
+        // negative case
+        assert_rejects(k, &circuit, vec![vec![sum + Fp::one()]]);
+    }
+}
+
+#[test]
+fn running_sum() {
+    let k = 4;
+
+    let inputs = (1..=8).map(Fp::from).collect::<Vec<_>>();
+    let sum = inputs.iter().sum::<Fp>();
+
+    let inputs = inputs.into_iter().map(Value::known).collect::<Vec<_>>();
+    let circuit = TestCircuit::new(move |field_chip, mut layouter| {
+        let sum = field_chip.running_sum(layouter.namespace(|| "sum"), &inputs)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose sum"), sum, 0, 0)
+    });
+    assert_accepts(k, &circuit, vec![vec![sum]]);
+
+    // negative case
+    assert_rejects(k, &circuit, vec![vec![sum + Fp::one()]]);
+}
+
+#[test]
+fn eval_poly() {
+    let k = 5;
+    let x = Value::known(Fp::from(3));
+
+    // p(x) = 1 + 2x + 5x^2, so p(3) = 52
+    let circuit = EvalPolyCircuit::new([1, 2, 5].map(field).to_vec(), x);
+    assert_accepts(k, &circuit, vec![vec![Fp::from(52)]]);
+
+    // negative case
+    assert_rejects(k, &circuit, vec![vec![Fp::from(53)]]);
+
+    // the empty polynomial is 0, and a constant polynomial is its coefficient
+    let circuit = EvalPolyCircuit::new(vec![], x);
+    assert_accepts(k, &circuit, vec![vec![Fp::zero()]]);
+    let circuit = EvalPolyCircuit::new(vec![field(7)], x);
+    assert_accepts(k, &circuit, vec![vec![Fp::from(7)]]);
+}
+
+#[test]
+fn compute() {
+    let k = 4;
+
+    let constant = Fp::from(7);
+    let a = Value::known(Fp::from(2));
+    let b = Value::known(Fp::from(3));
+
+    let circuit = |ops: Vec<Op<Fp>>| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let out = field_chip.compute(layouter.namespace(|| "compute"), &ops)?;
+
+            field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0, 0)
+        })
+    };
+
+    // ((a * b)^2) * constant, as computed by `MyCircuit`
+    let c = Fp::from(252);
+    assert_accepts(k, &MyCircuit::new(constant, a, b), vec![vec![c]]);
+
+    let circuit_ok = circuit(vec![
+        Op::Private(a),
+        Op::Private(b),
+        Op::Mul,
+        Op::Square,
+        Op::Const(constant),
+        Op::Mul,
+    ]);
+    assert_accepts(k, &circuit_ok, vec![vec![c]]);
+
+    // negative case
+    assert_rejects(k, &circuit_ok, vec![vec![c + Fp::one()]]);
+
+    // `a - a + b` exercises `Dup` and `Sub`
+    let dup_sub = circuit(vec![
+        Op::Private(a),
+        Op::Dup,
+        Op::Sub,
+        Op::Private(b),
+        Op::Add,
+    ]);
+    assert_accepts(k, &dup_sub, vec![vec![Fp::from(3)]]);
+
+    // malformed programs fail to synthesize
+    for ops in [vec![Op::Mul], vec![Op::Private(a), Op::Private(b)], vec![]] {
+        assert!(MockProver::run(k, &circuit(ops), vec![vec![c]]).is_err());
+    }
+}
+
+#[test]
+fn fold() {
+    let k = 5;
+
+    let circuit = |inputs: Vec<Value<Fp>>, op| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let inputs =
+                field_chip.load_private_many(layouter.namespace(|| "load inputs"), &inputs)?;
+
+            let out = field_chip.fold(layouter.namespace(|| "fold"), &inputs, op)?;
+
+            field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0, 0)
+        })
+    };
+
+    let inputs = values::<Fp>(&[2, 3, 4, 5]);
+    for (op, out) in [(FoldOp::Sum, 14), (FoldOp::Product, 120)] {
+        let circuit = circuit(inputs.clone(), op);
+        assert_accepts(k, &circuit, vec![vec![field(out)]]);
+
+        // negative case
+        assert_rejects(k, &circuit, vec![vec![field(out + 1)]]);
+    }
+
+    // the empty slice folds to the identity
+    for (op, out) in [(FoldOp::Sum, 0), (FoldOp::Product, 1)] {
+        assert_accepts(k, &circuit(vec![], op), vec![vec![field(out)]]);
+    }
+}
+
+#[test]
+fn sum_of_squares() {
+    let k = 4;
+
+    let circuit = SumOfSquaresCircuit::new(Value::known(Fp::from(3)), Value::known(Fp::from(4)));
+    assert_accepts(k, &circuit, vec![vec![Fp::from(25)]]);
+
+    // negative case: (a + b)^2
+    assert_rejects(k, &circuit, vec![vec![Fp::from(49)]]);
+}
+
+#[test]
+fn assert_linear_combo() {
+    let k = 4;
+
+    // 2x + 3y == 13
+    let circuit = |x: u64, y: u64| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let x = field_chip
+                .load_private(layouter.namespace(|| "load x"), Value::known(Fp::from(x)))?;
+            let y = field_chip
+                .load_private(layouter.namespace(|| "load y"), Value::known(Fp::from(y)))?;
+
+            field_chip.assert_linear_combo(
+                layouter.namespace(|| "2x + 3y == rhs"),
+                &[(Fp::from(2), x), (Fp::from(3), y)],
+                Fp::from(13),
+            )
+        })
+    };
+
+    // 2 * 2 + 3 * 3 == 13
+    assert_accepts(k, &circuit(2, 3), vec![vec![]]);
+
+    // negative case
+    assert_rejects(k, &circuit(3, 2), vec![vec![]]);
+}
+
+#[test]
+fn weighted_mean() {
+    let k = 5;
+
+    let circuit = |xs: Vec<Value<Fp>>, weights: Vec<Fp>| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let xs = field_chip.load_private_many(layouter.namespace(|| "load xs"), &xs)?;
+
+            let mean = field_chip.weighted_mean(layouter.namespace(|| "mean"), &xs, &weights)?;
+
+            field_chip.expose_public(layouter.namespace(|| "expose mean"), mean, 0, 0)
+        })
+    };
+
+    let xs = values::<Fp>(&[1, 4, 10]);
+
+    // (2 * 1 + 1 * 4 + 1 * 10) / 4
+    let circuit_ok = circuit(xs.clone(), vec![Fp::from(2), Fp::from(1), Fp::from(1)]);
+    assert_accepts(k, &circuit_ok, vec![vec![Fp::from(4)]]);
+
+    // negative case
+    assert_rejects(k, &circuit_ok, vec![vec![Fp::from(5)]]);
+
+    // mismatched lengths and a zero total weight fail while laying out the circuit
+    let mismatched = circuit(xs.clone(), vec![Fp::one(); 2]);
+    assert!(MockProver::run(k, &mismatched, vec![vec![Fp::zero()]]).is_err());
+    let zero_weight = circuit(xs, vec![Fp::one(), -Fp::one(), Fp::zero()]);
+    assert!(MockProver::run(k, &zero_weight, vec![vec![Fp::zero()]]).is_err());
+}
+
+#[test]
+fn running_product() {
+    let k = 4;
+
+    let circuit = |inputs: Vec<Value<Fp>>| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let product = field_chip.running_product(layouter.namespace(|| "product"), &inputs)?;
+
+            field_chip.expose_public(layouter.namespace(|| "expose product"), product, 0, 0)
+        })
+    };
+
+    let inputs = (1..=6).map(Fp::from).collect::<Vec<_>>();
+    let product = inputs.iter().product::<Fp>();
+    assert_eq!(product, Fp::from(720));
+
+    let circuit_ok = circuit(inputs.into_iter().map(Value::known).collect());
+    assert_accepts(k, &circuit_ok, vec![vec![product]]);
+
+    // negative case
+    assert_rejects(k, &circuit_ok, vec![vec![product + Fp::one()]]);
+
+    // the empty product
+    assert_accepts(k, &circuit(vec![]), vec![vec![Fp::one()]]);
+}
@@ -0,0 +1,505 @@
+//! Tests of the arithmetic instructions.
+
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{Chip, Layouter, Value},
+    dev::MockProver,
+    pasta::Fp,
+    plonk::{Circuit, ConstraintSystem},
+};
+
+use crate::{
+    circuits::ProductCircuit,
+    field_ext::{field, values},
+    test_util::{assert_accepts, assert_rejects, layout, smallest_k, TestCircuit},
+    FieldChip, FieldConfig, Instructions, Number,
+};
+
+#[test]
+fn add() {
+    let k = 4;
+
+    let a = Fp::from(2);
+    let b = Fp::from(3);
+
+    let circuit = TestCircuit::new(move |field_chip, mut layouter| {
+        let a = field_chip.load_private(layouter.namespace(|| "load a"), Value::known(a))?;
+        let b = field_chip.load_private(layouter.namespace(|| "load b"), Value::known(b))?;
+
+        let c = field_chip.add(layouter.namespace(|| "a + b"), a, b)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose c"), c, 0, 0)
+    });
+
+    let mut public_inputs = vec![a + b];
+    assert_accepts(k, &circuit, vec![public_inputs.clone()]);
+
+    // negative case
+    public_inputs[0] += Fp::one();
+    assert_rejects(k, &circuit, vec![public_inputs]);
+}
+
+#[test]
+fn sub() {
+    let k = 4;
+
+    let a = Fp::from(3);
+    let b = Fp::from(5);
+
+    let circuit = TestCircuit::new(move |field_chip, mut layouter| {
+        let a = field_chip.load_private(layouter.namespace(|| "load a"), Value::known(a))?;
+        let b = field_chip.load_private(layouter.namespace(|| "load b"), Value::known(b))?;
+
+        let c = field_chip.sub(layouter.namespace(|| "a - b"), a, b)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose c"), c, 0, 0)
+    });
+
+    let mut public_inputs = vec![a - b];
+    assert_accepts(k, &circuit, vec![public_inputs.clone()]);
+
+    // negative case: corrupt the expected difference
+    public_inputs[0] += Fp::one();
+    assert_rejects(k, &circuit, vec![public_inputs]);
+}
+
+#[test]
+fn square() {
+    let k = 4;
+
+    let x = Fp::from(5);
+
+    let circuit = |via_mul| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let x = field_chip.load_private(layouter.namespace(|| "load x"), Value::known(x))?;
+
+            let xsq = if via_mul {
+                field_chip.mul(layouter.namespace(|| "x * x"), x.clone(), x)?
+            } else {
+                field_chip.square(layouter.namespace(|| "x * x"), x)?
+            };
+
+            field_chip.expose_public(layouter.namespace(|| "expose xsq"), xsq, 0, 0)
+        })
+    };
+
+    let mut public_inputs = vec![x.square()];
+    assert_accepts(k, &circuit(false), vec![public_inputs.clone()]);
+
+    // the dedicated gate copies `x` in once, where `mul(x, x)` copies it in twice
+    let constants = |config: &FieldConfig| vec![config.constant];
+    let square = layout(&circuit(false), constants);
+    let via_mul = layout(&circuit(true), constants);
+    assert_eq!(square.rows(), via_mul.rows());
+    assert_eq!(square.copies() + 1, via_mul.copies());
+
+    // negative case
+    public_inputs[0] += Fp::one();
+    assert_rejects(k, &circuit(false), vec![public_inputs]);
+}
+
+#[test]
+fn mul_add() {
+    let k = 4;
+
+    let a = Value::known(Fp::from(2));
+    let b = Value::known(Fp::from(3));
+    let c = Value::known(Fp::from(4));
+
+    let circuit = |composed| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let a = field_chip.load_private(layouter.namespace(|| "load a"), a)?;
+            let b = field_chip.load_private(layouter.namespace(|| "load b"), b)?;
+            let c = field_chip.load_private(layouter.namespace(|| "load c"), c)?;
+
+            let out = if composed {
+                let ab = field_chip.mul(layouter.namespace(|| "a * b"), a, b)?;
+                field_chip.add(layouter.namespace(|| "ab + c"), ab, c)?
+            } else {
+                field_chip.mul_add(layouter.namespace(|| "a * b + c"), a, b, c)?
+            };
+
+            field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0, 0)
+        })
+    };
+
+    // both circuits must agree on the result
+    let mut public_inputs = vec![Fp::from(10)];
+    for composed in [false, true] {
+        assert_accepts(k, &circuit(composed), vec![public_inputs.clone()]);
+    }
+
+    // negative case
+    public_inputs[0] += Fp::one();
+    assert_rejects(k, &circuit(false), vec![public_inputs]);
+}
+
+#[test]
+fn wide_mul() {
+    let k = 4;
+
+    let a = Fp::from(2);
+    let b = Fp::from(3);
+
+    let circuit = TestCircuit::<_, _, 3>::with_columns(move |field_chip, mut layouter| {
+        let a = field_chip.load_private(layouter.namespace(|| "load a"), Value::known(a))?;
+        let b = field_chip.load_private(layouter.namespace(|| "load b"), Value::known(b))?;
+
+        let c = field_chip.mul(layouter.namespace(|| "a * b"), a, b)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose c"), c, 0, 0)
+    });
+
+    let mut public_inputs = vec![a * b];
+    assert_accepts(k, &circuit, vec![public_inputs.clone()]);
+
+    // negative case
+    public_inputs[0] += Fp::one();
+    assert_rejects(k, &circuit, vec![public_inputs]);
+}
+
+#[test]
+fn add_constant() {
+    let k = 4;
+
+    let x = Fp::from(3);
+    let constant = Fp::from(5);
+
+    let circuit = TestCircuit::new(move |field_chip, mut layouter| {
+        let x = field_chip.load_private(layouter.namespace(|| "load x"), Value::known(x))?;
+
+        let out = field_chip.add_constant(layouter.namespace(|| "x + k"), x, constant)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0, 0)
+    });
+
+    let mut public_inputs = vec![x + constant];
+    assert_accepts(k, &circuit, vec![public_inputs.clone()]);
+
+    // negative case
+    public_inputs[0] += Fp::one();
+    assert_rejects(k, &circuit, vec![public_inputs]);
+}
+
+#[test]
+fn mul_constant() {
+    let k = 4;
+
+    let x = Fp::from(3);
+    let constant = Fp::from(7);
+
+    let circuit = |via_mul| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let x = field_chip.load_private(layouter.namespace(|| "load x"), Value::known(x))?;
+
+            let out = if via_mul {
+                let constant =
+                    field_chip.load_constant(layouter.namespace(|| "load k"), constant)?;
+                field_chip.mul(layouter.namespace(|| "k * x"), constant, x)?
+            } else {
+                field_chip.mul_constant(layouter.namespace(|| "k * x"), x, constant)?
+            };
+
+            field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0, 0)
+        })
+    };
+
+    // both circuits must agree on the result
+    let mut public_inputs = vec![constant * x];
+    for via_mul in [false, true] {
+        assert_accepts(k, &circuit(via_mul), vec![public_inputs.clone()]);
+    }
+
+    // negative case
+    public_inputs[0] += Fp::one();
+    assert_rejects(k, &circuit(false), vec![public_inputs]);
+}
+
+#[test]
+fn pow_const() {
+    let k = 5;
+    let x = Fp::from(3);
+
+    for exp in [0, 1, 7, 256] {
+        let circuit = TestCircuit::new(move |field_chip, mut layouter| {
+            let x = field_chip.load_private(layouter.namespace(|| "load x"), Value::known(x))?;
+
+            let out = field_chip.pow_const(layouter.namespace(|| "x^exp"), x, exp)?;
+
+            field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0, 0)
+        });
+        let out = x.pow_vartime([exp]);
+
+        assert_accepts(k, &circuit, vec![vec![out]]);
+
+        // negative case
+        assert_rejects(k, &circuit, vec![vec![out + Fp::one()]]);
+    }
+}
+
+#[test]
+fn arith() {
+    let k = 4;
+
+    let a = Fp::from(2);
+    let b = Fp::from(3);
+
+    let circuit = |mode| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let a = field_chip.load_private(layouter.namespace(|| "load a"), Value::known(a))?;
+            let b = field_chip.load_private(layouter.namespace(|| "load b"), Value::known(b))?;
+
+            let out = field_chip.arith(layouter.namespace(|| "a op b"), a, b, mode)?;
+
+            field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0, 0)
+        })
+    };
+
+    // mode 0 adds, mode 1 multiplies
+    for (mode, out) in [(Fp::zero(), a + b), (Fp::one(), a * b)] {
+        assert_accepts(k, &circuit(mode), vec![vec![out]]);
+
+        // negative case
+        assert_rejects(k, &circuit(mode), vec![vec![out + Fp::one()]]);
+    }
+
+    // negative case: any other mode is rejected, even with the matching output
+    let mode = Fp::from(2);
+    let out = mode * a * b + (Fp::one() - mode) * (a + b);
+    assert_rejects(k, &circuit(mode), vec![vec![out]]);
+}
+
+#[test]
+fn div() {
+    let k = 4;
+
+    let circuit = |a: u64, b: u64| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let a = field_chip
+                .load_private(layouter.namespace(|| "load a"), Value::known(Fp::from(a)))?;
+            let b = field_chip
+                .load_private(layouter.namespace(|| "load b"), Value::known(Fp::from(b)))?;
+
+            let q = field_chip.div(layouter.namespace(|| "a / b"), a, b)?;
+
+            field_chip.expose_public(layouter.namespace(|| "expose q"), q, 0, 0)
+        })
+    };
+
+    assert_accepts(k, &circuit(6, 3), vec![vec![Fp::from(2)]]);
+
+    // negative case: the wrong quotient
+    assert_rejects(k, &circuit(6, 3), vec![vec![Fp::from(3)]]);
+
+    // dividing by zero fails while assigning the witness
+    assert!(MockProver::run(k, &circuit(6, 0), vec![vec![Fp::zero()]]).is_err());
+
+    // a prover laying out `0 / 0` by hand can't pick the quotient, since no `b_inv` works
+    for b_inv in [Fp::zero(), Fp::one(), Fp::from(5)] {
+        let circuit = TestCircuit::new(move |field_chip, mut layouter| {
+            let config = field_chip.config();
+
+            let q = layouter.assign_region(
+                || "div",
+                |mut region| {
+                    config.s_div.enable(&mut region, 0)?;
+
+                    let zero = Value::known(Fp::zero());
+                    region.assign_advice(|| "a", config.advice[0], 0, || zero)?;
+                    region.assign_advice(|| "b", config.advice[1], 0, || zero)?;
+                    region.assign_advice(
+                        || "1 / b",
+                        config.advice[1],
+                        1,
+                        || Value::known(b_inv),
+                    )?;
+                    region
+                        .assign_advice(
+                            || "a / b",
+                            config.advice[0],
+                            1,
+                            || Value::known(Fp::from(5)),
+                        )
+                        .map(Number)
+                },
+            )?;
+
+            field_chip.expose_public(layouter.namespace(|| "expose q"), q, 0, 0)
+        });
+        assert_rejects(k, &circuit, vec![vec![Fp::from(5)]]);
+    }
+}
+
+#[test]
+fn inv() {
+    let k = 4;
+
+    let circuit = |x: u64| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let x = field_chip
+                .load_private(layouter.namespace(|| "load x"), Value::known(Fp::from(x)))?;
+
+            let inv = field_chip.inv(layouter.namespace(|| "1 / x"), x.clone())?;
+            let one = field_chip.mul(layouter.namespace(|| "inv * x"), inv, x)?;
+
+            field_chip.expose_public(layouter.namespace(|| "expose one"), one, 0, 0)
+        })
+    };
+
+    assert_accepts(k, &circuit(5), vec![vec![Fp::one()]]);
+
+    // negative case
+    assert_rejects(k, &circuit(5), vec![vec![Fp::zero()]]);
+
+    // zero has no inverse, which fails while assigning the witness
+    assert!(MockProver::run(k, &circuit(0), vec![vec![Fp::one()]]).is_err());
+}
+
+#[test]
+fn cube() {
+    let k = 4;
+
+    let x = Fp::from(5);
+
+    let circuit = |composed| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let x = field_chip.load_private(layouter.namespace(|| "load x"), Value::known(x))?;
+
+            let xcube = if composed {
+                let xsq = field_chip.mul(layouter.namespace(|| "x * x"), x.clone(), x.clone())?;
+                field_chip.mul(layouter.namespace(|| "x * x * x"), xsq, x)?
+            } else {
+                field_chip.cube(layouter.namespace(|| "x * x * x"), x)?
+            };
+
+            field_chip.expose_public(layouter.namespace(|| "expose xcube"), xcube, 0, 0)
+        })
+    };
+
+    let mut public_inputs = vec![x.pow_vartime([3])];
+    assert_accepts(k, &circuit(false), vec![public_inputs.clone()]);
+    assert_accepts(k, &circuit(true), vec![public_inputs.clone()]);
+
+    // the single region must not need more rows than the two multiplications
+    assert!(
+        smallest_k(&circuit(false), vec![public_inputs.clone()])
+            <= smallest_k(&circuit(true), vec![public_inputs.clone()])
+    );
+
+    // negative case
+    public_inputs[0] += Fp::one();
+    assert_rejects(k, &circuit(false), vec![public_inputs]);
+}
+
+#[test]
+fn neg() {
+    let k = 4;
+
+    let x = Fp::from(5);
+
+    // neg(neg(x)) == x
+    let circuit = TestCircuit::new(move |field_chip, mut layouter| {
+        let x = field_chip.load_private(layouter.namespace(|| "load x"), Value::known(x))?;
+
+        let neg = field_chip.neg(layouter.namespace(|| "-x"), x)?;
+        let negneg = field_chip.neg(layouter.namespace(|| "-(-x)"), neg.clone())?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose neg"), neg, 0, 0)?;
+        field_chip.expose_public(layouter.namespace(|| "expose negneg"), negneg, 0, 1)
+    });
+    assert_accepts(k, &circuit, vec![vec![-x, x]]);
+
+    // negative case: a wrong negation
+    assert_rejects(k, &circuit, vec![vec![-x + Fp::one(), x]]);
+    assert_rejects(k, &circuit, vec![vec![x, x]]);
+}
+
+#[test]
+fn double() {
+    let k = 4;
+
+    let circuit = TestCircuit::new(|field_chip, mut layouter| {
+        let x =
+            field_chip.load_private(layouter.namespace(|| "load x"), Value::known(Fp::from(3)))?;
+
+        let out = field_chip.double(layouter.namespace(|| "x + x"), x)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0, 0)
+    });
+    assert_accepts(k, &circuit, vec![vec![Fp::from(6)]]);
+
+    // negative case
+    assert_rejects(k, &circuit, vec![vec![Fp::from(9)]]);
+}
+
+#[test]
+fn shift_left() {
+    let k = 4;
+
+    let x = Fp::from(5);
+
+    let circuit = |shift| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let x = field_chip.load_private(layouter.namespace(|| "load x"), Value::known(x))?;
+
+            let out = field_chip.shift_left(layouter.namespace(|| "x << k"), x, shift)?;
+
+            field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0, 0)
+        })
+    };
+
+    for (shift, factor) in [(0, 1), (1, 2), (3, 8)] {
+        assert_accepts(k, &circuit(shift), vec![vec![x * Fp::from(factor)]]);
+    }
+
+    // negative case
+    assert_rejects(k, &circuit(3), vec![vec![x * Fp::from(4)]]);
+}
+
+#[test]
+fn mul_rows() {
+    let k = 4;
+
+    // the rows halo2 leaves usable at `k` after reserving the blinding rows
+    let mut meta = ConstraintSystem::<Fp>::default();
+    ProductCircuit::<Fp>::configure(&mut meta);
+    let usable_rows = (1 << k) - meta.blinding_factors() - 1;
+
+    // a product of `n` inputs loads each of them and multiplies `n - 1` times. The MockProver
+    // only lays the circuit out when the reported rows fit.
+    for n in 1..8 {
+        let rows = n * FieldChip::<Fp>::LOAD_ROWS + (n - 1) * FieldChip::<Fp>::MUL_ROWS;
+        let product = (1..=n as u64).product::<u64>();
+
+        let circuit = ProductCircuit::new(values::<Fp>(&(1..=n as u64).collect::<Vec<_>>()));
+        let result = MockProver::run(k, &circuit, vec![vec![field(product)]]);
+        assert_eq!(result.is_ok(), rows <= usable_rows, "{} inputs", n);
+    }
+}
+
+#[test]
+fn sqrt() {
+    let k = 4;
+
+    let circuit = |x: Fp| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let x = field_chip.load_private(layouter.namespace(|| "load x"), Value::known(x))?;
+
+            let r = field_chip.sqrt(layouter.namespace(|| "sqrt(x)"), x)?;
+            let x = field_chip.square(layouter.namespace(|| "r^2"), r)?;
+
+            field_chip.expose_public(layouter.namespace(|| "expose x"), x, 0, 0)
+        })
+    };
+
+    let x = Fp::from(9);
+    assert_accepts(k, &circuit(x), vec![vec![x]]);
+
+    // negative case
+    assert_rejects(k, &circuit(x), vec![vec![x + Fp::one()]]);
+
+    // 5 is a non-residue, which fails while assigning the witness
+    let non_residue = Fp::from(5);
+    assert!(bool::from(non_residue.sqrt().is_none()));
+    assert!(MockProver::run(k, &circuit(non_residue), vec![vec![non_residue]]).is_err());
+}
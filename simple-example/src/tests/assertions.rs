@@ -0,0 +1,412 @@
+//! Tests of the instructions constraining or comparing values.
+
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{Layouter, Value},
+    dev::MockProver,
+    pasta::{group::ff::PrimeField, Fp},
+};
+
+use crate::{
+    circuits::{FactorizationCircuit, QuadraticRootCircuit},
+    field_ext::{field, values},
+    test_util::{assert_accepts, assert_rejects, TestCircuit},
+    FieldChip, Instructions,
+};
+
+#[test]
+fn is_zero() {
+    let k = 4;
+
+    for (x, out) in [(Fp::zero(), Fp::one()), (Fp::from(5), Fp::zero())] {
+        let circuit = TestCircuit::new(move |field_chip, mut layouter| {
+            let x = field_chip.load_private(layouter.namespace(|| "load x"), Value::known(x))?;
+
+            let out = field_chip.is_zero(layouter.namespace(|| "x == 0"), x)?;
+
+            field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0, 0)
+        });
+
+        assert_accepts(k, &circuit, vec![vec![out]]);
+
+        // negative case: the opposite answer
+        assert_rejects(k, &circuit, vec![vec![Fp::one() - out]]);
+    }
+}
+
+#[test]
+fn assert_bit() {
+    let k = 4;
+
+    let circuit = |x: u64| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let x = field_chip
+                .load_private(layouter.namespace(|| "load x"), Value::known(Fp::from(x)))?;
+
+            field_chip.assert_bit(layouter.namespace(|| "x is a bit"), x)
+        })
+    };
+
+    for x in [0, 1] {
+        assert_accepts(k, &circuit(x), vec![vec![]]);
+    }
+
+    // negative case: 2 is not a bit
+    assert_rejects(k, &circuit(2), vec![vec![]]);
+}
+
+#[test]
+fn select() {
+    let k = 4;
+
+    let a = Fp::from(2);
+    let b = Fp::from(3);
+
+    let circuit = |cond| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let cond =
+                field_chip.load_private(layouter.namespace(|| "load cond"), Value::known(cond))?;
+            let a = field_chip.load_private(layouter.namespace(|| "load a"), Value::known(a))?;
+            let b = field_chip.load_private(layouter.namespace(|| "load b"), Value::known(b))?;
+
+            let out = field_chip.select(layouter.namespace(|| "cond ? a : b"), cond, a, b)?;
+
+            field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0, 0)
+        })
+    };
+
+    for (cond, out) in [(Fp::one(), a), (Fp::zero(), b)] {
+        assert_accepts(k, &circuit(cond), vec![vec![out]]);
+    }
+
+    // negative case: a non-boolean `cond` satisfies the select gate but not the boolean one
+    let cond = Fp::from(2);
+    assert_rejects(k, &circuit(cond), vec![vec![cond * (a - b) + b]]);
+}
+
+#[test]
+fn assert_equal() {
+    let k = 4;
+
+    let a = Fp::from(2);
+    let b = Fp::from(3);
+
+    let circuit = |expected| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let a = field_chip.load_private(layouter.namespace(|| "load a"), Value::known(a))?;
+            let b = field_chip.load_private(layouter.namespace(|| "load b"), Value::known(b))?;
+            let expected = field_chip.load_private(
+                layouter.namespace(|| "load expected"),
+                Value::known(expected),
+            )?;
+
+            let ab = field_chip.mul(layouter.namespace(|| "a * b"), a, b)?;
+
+            field_chip.assert_equal(layouter.namespace(|| "ab == expected"), ab, expected)
+        })
+    };
+
+    assert_accepts(k, &circuit(a * b), vec![vec![]]);
+
+    // negative case: mismatched values
+    assert_rejects(k, &circuit(a * b + Fp::one()), vec![vec![]]);
+}
+
+#[test]
+fn decompose_bits() {
+    let k = 4;
+
+    // witnesses `bits` as given when they are set, instead of decomposing `x`
+    let circuit = |x: u64, n: usize, bits: Option<Vec<Value<Fp>>>| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let x = field_chip
+                .load_private(layouter.namespace(|| "load x"), Value::known(Fp::from(x)))?;
+
+            let bits = match &bits {
+                Some(bits) => field_chip.assign_bits(layouter.namespace(|| "x bits"), x, bits)?,
+                None => field_chip.decompose_bits(layouter.namespace(|| "x bits"), x, n)?,
+            };
+
+            for (row, bit) in bits.into_iter().enumerate() {
+                field_chip.expose_public(layouter.namespace(|| "expose bit"), bit, 0, row)?;
+            }
+            Ok(())
+        })
+    };
+
+    for x in [0u64, 1, 5, 10, 255] {
+        let bits = (0..8).map(|i| Fp::from((x >> i) & 1)).collect::<Vec<_>>();
+        assert_accepts(k, &circuit(x, 8, None), vec![bits]);
+    }
+
+    // negative cases: 256 does not fit in 8 bits, and a "bit" of 2 recomposes 2 but is not
+    // boolean
+    assert_rejects(k, &circuit(256, 8, None), vec![vec![Fp::zero(); 8]]);
+
+    let forged = circuit(2, 2, Some(values(&[2, 0])));
+    assert_rejects(k, &forged, vec![vec![Fp::from(2), Fp::zero()]]);
+
+    // as many bits as the field's capacity could wrap, which fails while assigning the witness
+    let n = Fp::CAPACITY as usize;
+    assert!(MockProver::run(k, &circuit(5, n, None), vec![vec![Fp::zero(); n]]).is_err());
+}
+
+#[test]
+fn is_equal_constant() {
+    let k = 4;
+
+    let constant = Fp::from(7);
+
+    for (x, out) in [(Fp::from(7), Fp::one()), (Fp::from(8), Fp::zero())] {
+        let circuit = TestCircuit::new(move |field_chip, mut layouter| {
+            let x = field_chip.load_private(layouter.namespace(|| "load x"), Value::known(x))?;
+
+            let out = field_chip.is_equal_constant(layouter.namespace(|| "x == k"), x, constant)?;
+
+            field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0, 0)
+        });
+
+        assert_accepts(k, &circuit, vec![vec![out]]);
+
+        // negative case: the opposite answer
+        assert_rejects(k, &circuit, vec![vec![Fp::one() - out]]);
+    }
+}
+
+#[test]
+fn assert_nonzero() {
+    let k = 4;
+
+    let circuit = |x| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let x = field_chip.load_private(layouter.namespace(|| "load x"), Value::known(x))?;
+
+            field_chip.assert_nonzero(layouter.namespace(|| "x != 0"), x)
+        })
+    };
+
+    for x in [Fp::one(), Fp::from(5), -Fp::one()] {
+        assert_accepts(k, &circuit(x), vec![vec![]]);
+    }
+
+    // negative case: zero fails while assigning the witness
+    assert!(MockProver::run(k, &circuit(Fp::zero()), vec![vec![]]).is_err());
+}
+
+#[test]
+fn assert_congruent() {
+    // the quotient's bits take most of the rows
+    let k = 6;
+    let modulus = Fp::from(4);
+
+    let circuit = |x: u64, y: u64| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let x =
+                field_chip.load_private(layouter.namespace(|| "load x"), Value::known(field(x)))?;
+            let y =
+                field_chip.load_private(layouter.namespace(|| "load y"), Value::known(field(y)))?;
+
+            field_chip.assert_congruent(layouter.namespace(|| "x == y mod m"), x, y, modulus)
+        })
+    };
+
+    // the quotient may be negative
+    for (x, y) in [(17, 5), (5, 17), (5, 5), (4, 0), (0, 4)] {
+        assert_accepts(k, &circuit(x, y), vec![vec![]]);
+    }
+
+    // negative cases: neither 17 - 6 nor 5 - 18 is a multiple of 4
+    for (x, y) in [(17, 6), (5, 18)] {
+        assert_rejects(k, &circuit(x, y), vec![vec![]]);
+    }
+}
+
+#[test]
+fn factorization() {
+    let k = 8;
+
+    let c = Fp::from(15);
+
+    // the verifier only learns `c`
+    let circuit = FactorizationCircuit::new(Value::known(Fp::from(3)), Value::known(Fp::from(5)));
+    assert_accepts(k, &circuit, vec![vec![c]]);
+
+    // negative case: factors of a different number
+    let circuit = FactorizationCircuit::new(Value::known(Fp::from(3)), Value::known(Fp::from(7)));
+    assert_rejects(k, &circuit, vec![vec![c]]);
+
+    // negative case: the trivial factorization has no witness for `1 - 1 != 0`
+    let circuit = FactorizationCircuit::new(Value::known(Fp::one()), Value::known(c));
+    assert!(MockProver::run(k, &circuit, vec![vec![c]]).is_err());
+
+    // negative case: factors that only multiply to `c` modulo the field, e.g. `c / 3` and 3 for a
+    // `c` that 3 doesn't divide
+    let c = Fp::from(16);
+    let a = c * Fp::from(3).invert().unwrap();
+    let circuit = FactorizationCircuit::new(Value::known(a), Value::known(Fp::from(3)));
+    assert_rejects(k, &circuit, vec![vec![c]]);
+}
+
+#[test]
+fn assert_in_set() {
+    let k = 4;
+
+    let circuit = |x: u64, allowed: Vec<Fp>| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let x = field_chip
+                .load_private(layouter.namespace(|| "load x"), Value::known(Fp::from(x)))?;
+
+            field_chip.assert_in_set(layouter.namespace(|| "x in set"), x.clone(), &allowed)?;
+
+            field_chip.expose_public(layouter.namespace(|| "expose x"), x, 0, 0)
+        })
+    };
+
+    let allowed = vec![Fp::from(2), Fp::from(3), Fp::from(5)];
+
+    for x in [2, 3, 5] {
+        assert_accepts(k, &circuit(x, allowed.clone()), vec![vec![Fp::from(x)]]);
+    }
+
+    // sets smaller than the limit are padded
+    assert_accepts(k, &circuit(3, vec![Fp::from(3)]), vec![vec![Fp::from(3)]]);
+
+    // negative case: a non-member
+    assert_rejects(k, &circuit(4, allowed), vec![vec![Fp::from(4)]]);
+
+    // the empty set and sets above the limit are rejected while laying out the circuit
+    let too_many = (1..=FieldChip::<Fp>::MAX_SET_SIZE as u64 + 1)
+        .map(Fp::from)
+        .collect();
+    for allowed in [vec![], too_many] {
+        assert!(MockProver::run(k, &circuit(1, allowed), vec![vec![Fp::one()]]).is_err());
+    }
+}
+
+#[test]
+fn commitment() {
+    let k = 4;
+
+    let (g, h) = (Fp::from(3), Fp::from(5));
+    let (m, r) = (Fp::from(11), Fp::from(13));
+    let commitment = g * m + h * r;
+
+    let circuit = |m| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let m = field_chip.load_private(layouter.namespace(|| "load m"), Value::known(m))?;
+            let r = field_chip.load_private(layouter.namespace(|| "load r"), Value::known(r))?;
+            let commitment = field_chip.load_public(layouter.namespace(|| "load commitment"), 0)?;
+
+            field_chip.check_commitment(
+                layouter.namespace(|| "check commitment"),
+                m,
+                r,
+                g,
+                h,
+                commitment,
+            )
+        })
+    };
+
+    assert_accepts(k, &circuit(m), vec![vec![commitment]]);
+
+    // negative cases: a tampered commitment, and a different message
+    assert_rejects(k, &circuit(m), vec![vec![commitment + Fp::one()]]);
+    assert_rejects(k, &circuit(m + Fp::one()), vec![vec![commitment]]);
+}
+
+#[test]
+fn parity() {
+    let k = 4;
+
+    let circuit = |x: u64| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let x = field_chip
+                .load_private(layouter.namespace(|| "load x"), Value::known(Fp::from(x)))?;
+
+            let parity = field_chip.parity(layouter.namespace(|| "x mod 2"), x, 4)?;
+
+            field_chip.expose_public(layouter.namespace(|| "expose parity"), parity, 0, 0)
+        })
+    };
+
+    for x in 0..8 {
+        let parity = Fp::from(x % 2);
+        assert_accepts(k, &circuit(x), vec![vec![parity]]);
+
+        // negative case: the other parity
+        assert_rejects(k, &circuit(x), vec![vec![Fp::one() - parity]]);
+    }
+
+    // a value that doesn't fit in 4 bits has no decomposition
+    assert_rejects(k, &circuit(16), vec![vec![Fp::zero()]]);
+}
+
+#[test]
+fn max() {
+    let k = 6;
+    let n = 4;
+
+    let circuit = |a: u64, b: u64| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let a = field_chip
+                .load_private(layouter.namespace(|| "load a"), Value::known(Fp::from(a)))?;
+            let b = field_chip
+                .load_private(layouter.namespace(|| "load b"), Value::known(Fp::from(b)))?;
+
+            let max = field_chip.max(layouter.namespace(|| "max(a, b)"), a, b, n)?;
+
+            field_chip.expose_public(layouter.namespace(|| "expose max"), max, 0, 0)
+        })
+    };
+
+    for (a, b) in [(3, 9), (9, 3), (5, 5), (0, 15)] {
+        assert_accepts(k, &circuit(a, b), vec![vec![Fp::from(a.max(b))]]);
+
+        // negative case: the smaller value
+        if a != b {
+            assert_rejects(k, &circuit(a, b), vec![vec![Fp::from(a.min(b))]]);
+        }
+    }
+
+    // negative case: an input above the bit bound
+    assert_rejects(k, &circuit(16, 3), vec![vec![Fp::from(16)]]);
+}
+
+#[test]
+fn quadratic_root() {
+    let k = 5;
+
+    // x^2 - 5x + 6 = (x - 2)(x - 3)
+    let (a, b, c) = (Fp::one(), -Fp::from(5), Fp::from(6));
+
+    for root in [2, 3] {
+        let circuit = QuadraticRootCircuit::new(Value::known(Fp::from(root)), a, b, c);
+        assert_accepts(k, &circuit, vec![vec![]]);
+    }
+
+    // negative case: a non-root
+    let circuit = QuadraticRootCircuit::new(Value::known(Fp::from(4)), a, b, c);
+    assert_rejects(k, &circuit, vec![vec![]]);
+}
+
+#[test]
+fn assert_product_equal() {
+    let k = 4;
+
+    let circuit = |inputs: [u64; 4]| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let nums = field_chip
+                .load_private_many(layouter.namespace(|| "load inputs"), &values::<Fp>(&inputs))?;
+            let [a, b, c, d] = <[_; 4]>::try_from(nums).expect("four inputs");
+
+            field_chip.assert_product_equal(layouter.namespace(|| "a * b == c * d"), a, b, c, d)
+        })
+    };
+
+    // 2 * 6 == 3 * 4
+    assert_accepts(k, &circuit([2, 6, 3, 4]), vec![vec![]]);
+
+    // negative case: 2 * 6 != 3 * 5
+    assert_rejects(k, &circuit([2, 6, 3, 5]), vec![vec![]]);
+}
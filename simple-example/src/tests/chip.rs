@@ -0,0 +1,193 @@
+//! Tests of `FieldChip` itself: cloning, gate descriptions and debug output.
+
+use std::cell::RefCell;
+
+use halo2_proofs::{
+    circuit::{Layouter, Value},
+    dev::CircuitGates,
+    pasta::Fp,
+    plonk::{keygen_vk, Circuit, ConstraintSystem},
+};
+
+use crate::{
+    prove,
+    test_util::{assert_accepts, assert_rejects, TestCircuit},
+    FieldChip, Instructions, MyCircuit,
+};
+
+#[test]
+fn debug_number() -> Result<(), Box<dyn std::error::Error>> {
+    let k = 4;
+
+    let a = Fp::from(2);
+    let b = Fp::from(3);
+
+    // the debug output of each product, one per synthesis pass
+    let trace = RefCell::new(vec![]);
+    let circuit = |a, b| {
+        let trace = &trace;
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let a = field_chip.load_private(layouter.namespace(|| "load a"), a)?;
+            let b = field_chip.load_private(layouter.namespace(|| "load b"), b)?;
+
+            let c = field_chip.mul(layouter.namespace(|| "a * b"), a, b)?;
+            trace.borrow_mut().push(format!("{:?}", c));
+
+            field_chip.expose_public(layouter.namespace(|| "expose c"), c, 0, 0)
+        })
+    };
+
+    assert_accepts(
+        k,
+        &circuit(Value::known(a), Value::known(b)),
+        vec![vec![a * b]],
+    );
+
+    assert!(!trace.borrow().is_empty());
+    assert!(trace.borrow()[0].contains(&format!("{:?}", a * b)));
+
+    // during key generation the value is unknown
+    trace.borrow_mut().clear();
+    keygen_vk(
+        &prove::params(),
+        &circuit(Value::unknown(), Value::unknown()),
+    )?;
+    assert!(trace.borrow()[0].contains("unknown"));
+
+    Ok(())
+}
+
+// lays out `a + b` and `a * b` through two clones of the same chip
+fn cloned_chip_circuit(a: Fp, b: Fp) -> impl Circuit<Fp> {
+    TestCircuit::new(move |field_chip, mut layouter| {
+        let cloned_chip = field_chip.clone();
+
+        let a = field_chip.load_private(layouter.namespace(|| "load a"), Value::known(a))?;
+        let b = cloned_chip.load_private(layouter.namespace(|| "load b"), Value::known(b))?;
+
+        let sum = field_chip.add(layouter.namespace(|| "a + b"), a.clone(), b.clone())?;
+        let product = cloned_chip.mul(layouter.namespace(|| "a * b"), a, b)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose sum"), sum, 0, 0)?;
+        cloned_chip.expose_public(layouter.namespace(|| "expose product"), product, 0, 1)
+    })
+}
+
+#[test]
+fn clone_chip() {
+    let k = 4;
+
+    let circuit = cloned_chip_circuit(Fp::from(2), Fp::from(3));
+    assert_accepts(k, &circuit, vec![vec![Fp::from(5), Fp::from(6)]]);
+
+    // negative case
+    assert_rejects(k, &circuit, vec![vec![Fp::from(6), Fp::from(5)]]);
+
+    // the debug output shows the configuration
+    let mut meta = ConstraintSystem::<Fp>::default();
+    let config = MyCircuit::<Fp>::configure(&mut meta);
+    let chip = FieldChip::<Fp>::construct(config);
+    let debug = format!("{:?}", chip.clone());
+    assert!(
+        debug.starts_with("FieldChip { config: FieldConfig {"),
+        "{}",
+        debug
+    );
+
+    // sharing the region counter between clones keeps the chip `Send` and `Sync`
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<FieldChip<Fp>>();
+}
+
+#[test]
+fn gate_descriptions() {
+    let descriptions = FieldChip::<Fp>::gate_descriptions();
+    assert!(descriptions
+        .contains(&"op: s_op * (mode * (lhs * rhs) + (1 - mode) * (lhs + rhs) - out)".to_string()));
+
+    // the constraints as collected from the circuit, in the same `"<gate>: <polynomial>"` form
+    let collected = CircuitGates::collect::<Fp, MyCircuit<Fp>>().to_string();
+    let mut gate = "";
+    let mut constraints = vec![];
+    for line in collected.lines() {
+        if let Some(polynomial) = line.strip_prefix("- ") {
+            constraints.push(format!("{}: {}", gate, polynomial));
+        } else if let Some(name) = line.strip_suffix(':') {
+            gate = name;
+        }
+    }
+
+    // each description belongs to the same gate, and applies the same operators, as the
+    // constraint it describes
+    let gate = |constraint: &str| constraint.split(':').next().map(str::to_string);
+    let operators =
+        |constraint: &str| [" * ", " + ", " - "].map(|op| constraint.matches(op).count());
+    assert_eq!(descriptions.len(), constraints.len());
+    for (description, constraint) in descriptions.iter().zip(&constraints) {
+        assert_eq!(gate(description), gate(constraint));
+        assert_eq!(
+            operators(description),
+            operators(constraint),
+            "{} does not match {}",
+            description,
+            constraint
+        );
+    }
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn tracing() -> Result<(), Box<dyn std::error::Error>> {
+    use std::{
+        io,
+        sync::{Arc, Mutex},
+    };
+
+    let k = 4;
+
+    // `tracing-test` is not in the registry this crate builds against, so the formatted logs are
+    // captured by a writer appending to a shared buffer
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let capture = Capture::default();
+    let writer = capture.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+
+    let circuit = MyCircuit::new(
+        Fp::from(7),
+        Value::known(Fp::from(2)),
+        Value::known(Fp::from(3)),
+    );
+    let cloned = cloned_chip_circuit(Fp::from(2), Fp::from(3));
+    tracing::subscriber::with_default(subscriber, || {
+        assert_accepts(k, &circuit, vec![vec![Fp::from(252)]]);
+        assert_accepts(k, &cloned, vec![vec![Fp::from(5), Fp::from(6)]]);
+    });
+
+    let logs = String::from_utf8(capture.0.lock().unwrap().clone())?;
+    assert!(logs.contains("mul{region=mul[3]}"));
+    assert!(logs.contains("load_private{region=load private[0]}"));
+    // the clone numbers its regions after the original's, rather than from 0 again
+    assert_eq!(
+        logs.matches("load_private{region=load private[1]}").count(),
+        2
+    );
+    assert!(logs.contains("expose_public{column=0 row=0}"));
+
+    Ok(())
+}
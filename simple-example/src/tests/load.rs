@@ -0,0 +1,338 @@
+//! Tests of the instructions loading and exposing values.
+
+use std::cell::RefCell;
+
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{Chip, Layouter, Value},
+    dev::MockProver,
+    pasta::Fp,
+};
+
+use crate::{
+    field_ext::{field, values},
+    prove,
+    test_util::{assert_accepts, assert_rejects, TestCircuit},
+    CircuitError, FoldOp, Instructions, MyCircuit,
+};
+
+#[test]
+fn constant() {
+    let k = 4;
+
+    let constant = Fp::from(7);
+
+    let circuit = TestCircuit::new(move |field_chip, mut layouter| {
+        let constant =
+            field_chip.load_constant(layouter.namespace(|| "load constant"), constant)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose constant"), constant, 0, 0)
+    });
+
+    assert_accepts(k, &circuit, vec![vec![constant]]);
+
+    // negative case: the loaded cell is bound to the fixed column, so a different constant
+    // cannot satisfy the public input
+    assert_rejects(k, &circuit, vec![vec![constant + Fp::one()]]);
+}
+
+#[test]
+fn load_public() {
+    let k = 4;
+
+    let x = Fp::from(6);
+    let y = Fp::from(7);
+
+    let circuit = TestCircuit::new(move |field_chip, mut layouter| {
+        let x = field_chip.load_private(layouter.namespace(|| "load x"), Value::known(x))?;
+        let y = field_chip.load_public(layouter.namespace(|| "load y"), 1)?;
+
+        let xy = field_chip.mul(layouter.namespace(|| "x * y"), x, y)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose xy"), xy, 0, 0)
+    });
+
+    // row 0 holds the product, row 1 the public operand
+    let mut public_inputs = vec![x * y, y];
+    assert_accepts(k, &circuit, vec![public_inputs.clone()]);
+
+    // negative case: changing the public operand invalidates the product
+    public_inputs[1] += Fp::one();
+    assert_rejects(k, &circuit, vec![public_inputs]);
+}
+
+#[test]
+fn expose_many() {
+    let k = 4;
+
+    let a = Fp::from(2);
+    let b = Fp::from(3);
+
+    let circuit = TestCircuit::new(move |field_chip, mut layouter| {
+        let a = field_chip.load_private(layouter.namespace(|| "load a"), Value::known(a))?;
+        let b = field_chip.load_private(layouter.namespace(|| "load b"), Value::known(b))?;
+
+        let sum = field_chip.add(layouter.namespace(|| "a + b"), a.clone(), b.clone())?;
+        let product = field_chip.mul(layouter.namespace(|| "a * b"), a, b)?;
+
+        field_chip.expose_public_many(
+            layouter.namespace(|| "expose outputs"),
+            &[sum, product],
+            0,
+        )?;
+        Ok(())
+    });
+
+    let mut public_inputs = vec![a + b, a * b];
+    assert_accepts(k, &circuit, vec![public_inputs.clone()]);
+
+    // negative case: swapped outputs
+    public_inputs.swap(0, 1);
+    assert_rejects(k, &circuit, vec![public_inputs]);
+}
+
+#[test]
+fn circuit_error() -> Result<(), Box<dyn std::error::Error>> {
+    let k = 4;
+
+    // records the errors returned by the slice instructions when misused
+    let errors = RefCell::new(vec![]);
+    let circuit = TestCircuit::new(|field_chip, mut layouter| {
+        let x =
+            field_chip.load_private(layouter.namespace(|| "load x"), Value::known(Fp::one()))?;
+
+        let mut errors = errors.borrow_mut();
+        if let Err(e) = field_chip.inner_product(
+            layouter.namespace(|| "mismatched"),
+            &[x.clone(), x.clone()],
+            std::slice::from_ref(&x),
+        ) {
+            errors.push(e);
+        }
+        if let Err(e) =
+            field_chip.expose_public_many(layouter.namespace(|| "out of range"), &[x], 1000)
+        {
+            errors.push(e);
+        }
+
+        Ok(())
+    });
+
+    MockProver::run(k, &circuit, vec![vec![]])?;
+
+    let errors = errors.borrow();
+    assert!(matches!(
+        errors[0],
+        CircuitError::LengthMismatch {
+            expected: 2,
+            got: 1
+        }
+    ));
+    assert!(matches!(errors[1], CircuitError::InvalidRow(1000)));
+
+    Ok(())
+}
+
+#[test]
+fn load_constants() {
+    let k = 4;
+
+    let constants = [Fp::from(2), Fp::from(3), Fp::from(4)];
+    let circuit = TestCircuit::new(move |field_chip, mut layouter| {
+        let x =
+            field_chip.load_private(layouter.namespace(|| "load x"), Value::known(Fp::from(5)))?;
+        let constants =
+            field_chip.load_constants(layouter.namespace(|| "load constants"), &constants)?;
+
+        let mut out = x;
+        for constant in constants {
+            out = field_chip.mul(layouter.namespace(|| "out * c"), out, constant)?;
+        }
+
+        field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0, 0)
+    });
+    assert_accepts(k, &circuit, vec![vec![Fp::from(120)]]);
+
+    // negative case
+    assert_rejects(k, &circuit, vec![vec![Fp::from(121)]]);
+}
+
+#[test]
+fn multi_instance() {
+    let k = 4;
+
+    let circuit = TestCircuit::<_, _, 2, 2>::with_columns(|field_chip, mut layouter| {
+        let a =
+            field_chip.load_private(layouter.namespace(|| "load a"), Value::known(Fp::from(2)))?;
+        let b =
+            field_chip.load_private(layouter.namespace(|| "load b"), Value::known(Fp::from(3)))?;
+
+        let sum = field_chip.add(layouter.namespace(|| "a + b"), a.clone(), b.clone())?;
+        let product = field_chip.mul(layouter.namespace(|| "a * b"), a, b)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose sum"), sum, 0, 0)?;
+        field_chip.expose_public(layouter.namespace(|| "expose product"), product, 1, 0)
+    });
+    assert_accepts(k, &circuit, vec![vec![Fp::from(5)], vec![Fp::from(6)]]);
+
+    // negative case: the columns swapped
+    assert_rejects(k, &circuit, vec![vec![Fp::from(6)], vec![Fp::from(5)]]);
+
+    // exposing into a column that doesn't exist fails to synthesize
+    let missing_column = TestCircuit::new(|field_chip, mut layouter| {
+        let x = field_chip.load_constant(layouter.namespace(|| "load x"), Fp::one())?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose x"), x, 1, 0)
+    });
+    assert!(MockProver::run(k, &missing_column, vec![vec![Fp::one()]]).is_err());
+}
+
+#[test]
+fn expose_public_cell() {
+    let k = 4;
+
+    // the third advice column isn't used by any gate of the chip
+    let circuit = TestCircuit::<_, _, 3>::with_columns(|field_chip, mut layouter| {
+        let column = field_chip.config().advice[2];
+
+        let cell = layouter.assign_region(
+            || "raw cell",
+            |mut region| region.assign_advice(|| "x + 1", column, 0, || Value::known(Fp::from(6))),
+        )?;
+
+        field_chip.expose_public_cell(layouter.namespace(|| "expose cell"), cell.cell(), 0, 0)
+    });
+    assert_accepts(k, &circuit, vec![vec![Fp::from(6)]]);
+
+    // negative case
+    assert_rejects(k, &circuit, vec![vec![Fp::from(5)]]);
+}
+
+#[test]
+fn load_constant_gated() {
+    let k = 4;
+
+    let constant = Fp::from(7);
+
+    // witnesses `tampered` in place of the constant when it is set
+    let circuit = |tampered: Option<Value<Fp>>| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let k = match tampered {
+                Some(value) => field_chip.assign_constant_gated(
+                    layouter.namespace(|| "load k"),
+                    constant,
+                    value,
+                )?,
+                None => {
+                    field_chip.load_constant_gated(layouter.namespace(|| "load k"), constant)?
+                }
+            };
+
+            field_chip.expose_public(layouter.namespace(|| "expose k"), k, 0, 0)
+        })
+    };
+
+    assert_accepts(k, &circuit(None), vec![vec![constant]]);
+
+    // negative case: a tampered advice value fails the gate, even when exposed as expected
+    let tampered = circuit(Some(Value::known(Fp::from(8))));
+    assert_rejects(k, &tampered, vec![vec![Fp::from(8)]]);
+}
+
+#[test]
+fn load_private_many() {
+    let k = 5;
+
+    let inputs = values::<Fp>(&[1, 2, 3, 4, 5, 6, 7, 8]);
+    let circuit = TestCircuit::new(move |field_chip, mut layouter| {
+        let inputs = field_chip.load_private_many(layouter.namespace(|| "load inputs"), &inputs)?;
+
+        let product =
+            field_chip.fold(layouter.namespace(|| "product"), &inputs, FoldOp::Product)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose product"), product, 0, 0)
+    });
+    assert_accepts(k, &circuit, vec![vec![field(40320)]]);
+
+    // negative case
+    assert_rejects(k, &circuit, vec![vec![field(40321)]]);
+}
+
+#[test]
+fn mul_and_expose() {
+    let k = 4;
+
+    // `MyCircuit` computes and exposes `c = constant * absq` with `mul_and_expose`
+    let circuit = MyCircuit::new(
+        Fp::from(7),
+        Value::known(Fp::from(2)),
+        Value::known(Fp::from(3)),
+    );
+    assert_accepts(k, &circuit, vec![vec![Fp::from(252)]]);
+
+    // the product goes to the requested row only
+    assert_rejects(k, &circuit, vec![vec![Fp::zero(), Fp::from(252)]]);
+
+    // negative case
+    assert_rejects(k, &circuit, vec![vec![Fp::from(253)]]);
+}
+
+#[test]
+fn load_one() {
+    let k = 4;
+
+    let x = Fp::from(42);
+
+    // 1 is the multiplicative identity
+    let circuit = TestCircuit::new(move |field_chip, mut layouter| {
+        let x = field_chip.load_private(layouter.namespace(|| "load x"), Value::known(x))?;
+        let one = field_chip.load_one(layouter.namespace(|| "load one"))?;
+
+        let x = field_chip.mul(layouter.namespace(|| "1 * x"), one, x)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose x"), x, 0, 0)
+    });
+    assert_accepts(k, &circuit, vec![vec![x]]);
+
+    // negative case
+    assert_rejects(k, &circuit, vec![vec![x + Fp::one()]]);
+
+    // `load_zero` backs the empty inner product
+    let circuit = TestCircuit::new(|field_chip, mut layouter| {
+        let out = field_chip.inner_product(layouter.namespace(|| "xs . ys"), &[], &[])?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose out"), out, 0, 0)
+    });
+    assert_accepts(k, &circuit, vec![vec![Fp::zero()]]);
+}
+
+#[test]
+fn load_private_bytes() {
+    let k = 4;
+
+    let x = Fp::from(0x0102_0304_0506_0708);
+    let bytes = prove::serialize_public(x);
+    assert_eq!(bytes.len(), 32);
+
+    let circuit = |bytes: Vec<u8>| {
+        TestCircuit::new(move |field_chip, mut layouter| {
+            let x = field_chip.load_private_bytes(layouter.namespace(|| "load x"), &bytes)?;
+
+            field_chip.expose_public(layouter.namespace(|| "expose x"), x, 0, 0)
+        })
+    };
+
+    assert_accepts(k, &circuit(bytes.clone()), vec![vec![x]]);
+
+    // negative case
+    assert_rejects(k, &circuit(bytes.clone()), vec![vec![x + Fp::one()]]);
+
+    // a value outside the field is reduced, i.e. 2^256 - 1 mod p
+    let max = Fp::from(2).pow([256]) - Fp::one();
+    assert_accepts(k, &circuit(vec![0xff; 32]), vec![vec![max]]);
+
+    // the wrong length fails while laying out the circuit
+    for bytes in [bytes[..31].to_vec(), [bytes.as_slice(), &[0]].concat()] {
+        assert!(MockProver::run(k, &circuit(bytes), vec![vec![x]]).is_err());
+    }
+}
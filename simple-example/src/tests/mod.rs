@@ -0,0 +1,8 @@
+//! Tests of the `FieldChip` instructions and `MyCircuit`.
+
+mod aggregate;
+mod arith;
+mod assertions;
+mod chip;
+mod load;
+mod my_circuit;
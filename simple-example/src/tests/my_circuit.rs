@@ -0,0 +1,223 @@
+//! Tests of `MyCircuit`.
+
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{floor_planner::V1, Value},
+    pasta::Fp,
+    plonk::{keygen_vk, Circuit},
+};
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+
+use crate::{
+    prove,
+    test_util::{assert_accepts, assert_rejects, smallest_k},
+    MyCircuit,
+};
+
+#[test]
+fn builder() -> Result<(), Box<dyn std::error::Error>> {
+    let constant = Fp::from(7);
+
+    // a witness-less circuit is enough for key generation
+    let empty_circuit = MyCircuit::builder().constant(constant).build();
+    let params = prove::params();
+    let vk = keygen_vk(&params, &empty_circuit)?;
+
+    // and its key verifies proofs for the fully populated circuit
+    let a = Fp::from(2);
+    let b = Fp::from(3);
+    let c = constant * a.square() * b.square();
+    let circuit = MyCircuit::builder()
+        .constant(constant)
+        .a(Value::known(a))
+        .b(Value::known(b))
+        .build();
+
+    let proof = prove::prove(&circuit, &[c])?;
+    assert!(prove::verify(&vk, &[c], &proof));
+
+    Ok(())
+}
+
+#[test]
+fn random_inputs() {
+    let k = 4;
+
+    // a fixed seed, so that a failing case reproduces on every run
+    let mut rng = ChaCha20Rng::seed_from_u64(30);
+
+    for _ in 0..32 {
+        let constant = Fp::random(&mut rng);
+        let a = Fp::random(&mut rng);
+        let b = Fp::random(&mut rng);
+        let c = constant * a.square() * b.square();
+
+        let circuit = MyCircuit::new(constant, Value::known(a), Value::known(b));
+
+        assert_accepts(k, &circuit, vec![vec![c]]);
+
+        // negative case: a random wrong public input
+        let wrong = Fp::random(&mut rng);
+        if wrong != c {
+            assert_rejects(k, &circuit, vec![vec![wrong]]);
+        }
+    }
+}
+
+#[test]
+fn my_circuit() {
+    let k = 4;
+
+    let constant = Fp::from(7);
+    let a = Fp::from(2);
+    let b = Fp::from(3);
+    let c = constant * a.square() * b.square();
+
+    let circuit = MyCircuit::new(constant, Value::known(a), Value::known(b));
+
+    let mut public_inputs = vec![c];
+    assert_accepts(k, &circuit, vec![public_inputs.clone()]);
+
+    // negative case
+    public_inputs[0] += Fp::one();
+    assert_rejects(k, &circuit, vec![public_inputs]);
+}
+
+#[test]
+fn min_k() {
+    let k = MyCircuit::<Fp>::min_k();
+    assert_eq!(k, 4);
+
+    let constant = Fp::from(7);
+    let a = Fp::from(2);
+    let b = Fp::from(3);
+    let circuit = MyCircuit::new(constant, Value::known(a), Value::known(b));
+    let public_inputs = vec![constant * a.square() * b.square()];
+
+    assert_accepts(k, &circuit, vec![public_inputs.clone()]);
+    assert_eq!(k, smallest_k(&circuit, vec![public_inputs]));
+}
+
+#[test]
+fn floor_planner() -> Result<(), Box<dyn std::error::Error>> {
+    let k = 4;
+
+    let constant = Fp::from(7);
+    let a = Fp::from(2);
+    let b = Fp::from(3);
+    let public_inputs = vec![constant * a.square() * b.square()];
+
+    let circuit =
+        MyCircuit::<Fp, V1>::with_floor_planner(constant, Value::known(a), Value::known(b));
+    assert_accepts(k, &circuit, vec![public_inputs.clone()]);
+
+    // the V1 layout still proves and verifies
+    let params = prove::params();
+    let pk = prove::keygen(&params, &circuit)?;
+    let proof = prove::prove(&circuit, &public_inputs)?;
+    assert!(prove::verify(pk.get_vk(), &public_inputs, &proof));
+
+    Ok(())
+}
+
+#[test]
+fn evaluate() -> Result<(), Box<dyn std::error::Error>> {
+    let k = 4;
+
+    let constant = Fp::from(7);
+    let a = Fp::from(2);
+    let b = Fp::from(3);
+
+    let circuit = MyCircuit::new(constant, Value::known(a), Value::known(b));
+    let c = circuit.evaluate().ok_or("the witnesses are known")?;
+    assert_eq!(c, constant * a.square() * b.square());
+    assert_accepts(k, &circuit, vec![vec![c]]);
+
+    // an unknown witness leaves the output unknown
+    let circuit = MyCircuit::new(constant, Value::known(a), Value::unknown());
+    assert_eq!(circuit.evaluate(), None);
+
+    Ok(())
+}
+
+#[test]
+fn strict() -> Result<(), Box<dyn std::error::Error>> {
+    let k = 4;
+
+    let constant = Fp::from(7);
+    let public_inputs = vec![vec![Fp::from(252)]];
+
+    // a strict circuit with known inputs is satisfied as usual
+    let circuit = MyCircuit::new(
+        constant,
+        Value::known(Fp::from(2)),
+        Value::known(Fp::from(3)),
+    );
+    assert_accepts(k, &circuit.strict(), public_inputs.clone());
+
+    // a partly unknown input is only rejected in strict mode. Synthesizing for `keygen_vk`
+    // never assigns the values, so a non-strict circuit lays out the unknown input silently.
+    let params = prove::params();
+    for circuit in [
+        MyCircuit::new(constant, Value::known(Fp::from(2)), Value::unknown()),
+        MyCircuit::new(constant, Value::unknown(), Value::known(Fp::from(3))),
+    ] {
+        assert!(keygen_vk(&params, &circuit).is_ok());
+        assert!(keygen_vk(&params, &circuit.strict()).is_err());
+    }
+
+    // key generation works the same in strict mode, with or without the witnesses
+    let circuit = MyCircuit::new(
+        constant,
+        Value::known(Fp::from(2)),
+        Value::known(Fp::from(3)),
+    )
+    .strict();
+    let pk = prove::keygen(&params, &circuit)?;
+    let vk = keygen_vk(&params, &circuit.without_witnesses())?;
+    assert_eq!(
+        format!("{:?}", vk.pinned()),
+        format!("{:?}", pk.get_vk().pinned())
+    );
+
+    Ok(())
+}
+
+#[test]
+fn fq() -> Result<(), Box<dyn std::error::Error>> {
+    use halo2_proofs::pasta::Fq;
+
+    let k = 4;
+
+    // the scalar field of the other curve of the Pasta cycle
+    let constant = Fq::from(7);
+    let a = Fq::from(2);
+    let b = Fq::from(3);
+
+    let circuit = MyCircuit::new(constant, Value::known(a), Value::known(b));
+    let c = circuit.evaluate().ok_or("the witnesses are known")?;
+    assert_eq!(c, Fq::from(252));
+    assert_accepts(k, &circuit, vec![vec![c]]);
+
+    // negative case
+    assert_rejects(k, &circuit, vec![vec![c + Fq::one()]]);
+
+    Ok(())
+}
+
+#[test]
+fn expected_public() {
+    let k = 4;
+
+    let constant = Fp::from(7);
+    let a = Fp::from(2);
+    let b = Fp::from(3);
+
+    let circuit = MyCircuit::new(constant, Value::known(a), Value::known(b));
+    let public_inputs = circuit.expected_public();
+    assert_eq!(public_inputs, vec![constant * a.square() * b.square()]);
+    assert_accepts(k, &circuit, vec![public_inputs]);
+
+    // there's no instance without the witnesses
+    assert!(MyCircuit::<Fp>::default().expected_public().is_empty());
+}
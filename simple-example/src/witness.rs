@@ -0,0 +1,86 @@
+//! Generating the inputs of `MyCircuit` separately from its structure.
+
+use halo2_proofs::{arithmetic::Field, circuit::Value};
+
+use crate::MyCircuit;
+
+// the constant and the private inputs of a `MyCircuit`
+#[derive(Debug, Clone, Copy)]
+pub struct CircuitInputs<F: Field> {
+    pub constant: F,
+    pub a: Value<F>,
+    pub b: Value<F>,
+}
+
+// Produces the inputs of a `MyCircuit`, e.g. fixed values, values read from a file, or random
+// values for testing
+pub trait WitnessSource<F: Field> {
+    fn witnesses(&self) -> CircuitInputs<F>;
+}
+
+// fixed inputs are their own source
+impl<F: Field> WitnessSource<F> for CircuitInputs<F> {
+    fn witnesses(&self) -> CircuitInputs<F> {
+        *self
+    }
+}
+
+impl<F: Field> MyCircuit<F> {
+    // build the circuit from the inputs produced by `source`
+    pub fn from_witnesses(source: &impl WitnessSource<F>) -> Self {
+        let CircuitInputs { constant, a, b } = source.witnesses();
+        Self::new(constant, a, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use halo2_proofs::pasta::Fp;
+    use rand_core::OsRng;
+
+    use crate::prove;
+
+    #[test]
+    fn witness_source() -> Result<(), Box<dyn std::error::Error>> {
+        // a source sampling fresh random inputs every time
+        struct RandomWitnesses;
+
+        impl WitnessSource<Fp> for RandomWitnesses {
+            fn witnesses(&self) -> CircuitInputs<Fp> {
+                CircuitInputs {
+                    constant: Fp::random(OsRng),
+                    a: Value::known(Fp::random(OsRng)),
+                    b: Value::known(Fp::random(OsRng)),
+                }
+            }
+        }
+
+        let fixed = CircuitInputs {
+            constant: Fp::from(7),
+            a: Value::known(Fp::from(2)),
+            b: Value::known(Fp::from(3)),
+        };
+
+        for circuit in [
+            MyCircuit::from_witnesses(&fixed),
+            MyCircuit::from_witnesses(&RandomWitnesses),
+        ] {
+            let public_inputs = [circuit.evaluate().ok_or("the witnesses are known")?];
+
+            let pk = prove::keygen(&prove::params(), &circuit)?;
+            let proof = prove::prove(&circuit, &public_inputs)?;
+            assert!(prove::verify(pk.get_vk(), &public_inputs, &proof));
+
+            // negative case
+            assert!(!prove::verify(
+                pk.get_vk(),
+                &[public_inputs[0] + Fp::one()],
+                &proof
+            ));
+        }
+
+        Ok(())
+    }
+}
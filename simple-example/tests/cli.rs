@@ -0,0 +1,39 @@
+//! Runs the `simple-example` binary and checks its output and exit codes.
+
+use std::process::{Command, Output};
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_simple-example"))
+        .args(args)
+        .output()
+        .expect("the binary runs")
+}
+
+#[test]
+fn accepts_defaults() {
+    let output = run(&[]);
+
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("verification passed"));
+}
+
+#[test]
+fn rejects_invalid_arguments() {
+    for args in [&["--a"][..], &["--a", "two"], &["--c", "3"]] {
+        let output = run(args);
+
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.starts_with("error: "), "{}", stderr);
+        assert!(stderr.contains("usage: simple-example"), "{}", stderr);
+    }
+}
+
+#[test]
+fn rejects_too_small_k() {
+    // the circuit doesn't fit in 2^2 rows
+    let output = run(&["--k", "2"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("verification failed"));
+}